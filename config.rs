@@ -1,13 +1,59 @@
 //! Compile time options.
 
+use crate::util::clip::Selection;
+
+use std::num::NonZeroUsize;
+
 /// Default time in seconds to keep an item in the clipboard. Should be a low
 /// value for security.
 pub const DEFAULT_CLIP_TIME: u64 = 10;
 
+/// The selections items are copied to by default.
+pub const DEFAULT_CLIP_SELECTION: Selection = Selection::Primary;
+
+/// Whether the previous contents of the clipboard are restored by default once
+/// the time to keep an item runs out.
+pub const RESTORE_CLIPBOARD: bool = false;
+
+/// Whether items are copied through the terminal with OSC 52 escape sequences
+/// by default, even if a display is available. This is always the case without
+/// a display, as in SSH sessions.
+pub const CLIP_THROUGH_TERMINAL: bool = false;
+
+/// Whether displayed items are erased from the terminal by default once the
+/// time to keep an item in the clipboard runs out, or a key is pressed.
+pub const TIMED_SHOW: bool = false;
+
+/// Whether displaying items is refused when standard output is not a
+/// terminal, as when piped to another program or a file. A warning is printed
+/// instead if false. Either can be bypassed explicitly.
+pub const REFUSE_NON_TERMINAL_SHOW: bool = false;
+
 /// The default item to view in a group (usually the password). Always an
 /// exact match.
 pub const DEFAULT_ITEM: &str = "password";
 
+/// The maximum number of candidates to show when a target matches several
+/// records equally well.
+pub const MAX_MATCH_CANDIDATES: usize = 10;
+
+/// The minimum score from 0 to 4 for a password not to be considered weak,
+/// where 3 means that it would resist an offline attack with a slow hash.
+pub const MIN_PASSWORD_SCORE: u8 = 3;
+
+/// The minimum score from 0 to 4 of the password of a pass file, below which it
+/// is refused unless explicitly allowed. See [`MIN_PASSWORD_SCORE`].
+pub const MIN_MASTER_PASSWORD_SCORE: u8 = 2;
+
+/// The default length of generated passwords.
+pub const DEFAULT_GEN_LENGTH: usize = 20;
+
+/// The default number of words in generated passphrases. Each word adds the
+/// base 2 logarithm of the size of the embedded word list to the entropy, 11
+/// bits with the current list, for 77 bits in total.
+pub const DEFAULT_PASSPHRASE_WORDS: NonZeroUsize =
+    NonZeroUsize::new(7).unwrap();
+
 /// Name of the default pass file containing encrypted data. Must be a valid
 /// file name.
 pub const DEFAULT_PASS_FILE_NAME: &str = "data.pass";
@@ -15,4 +61,5 @@ pub const DEFAULT_PASS_FILE_NAME: &str = "data.pass";
 // TODO: necessary ?
 /// The maximum number of times to prompt for the password if entered
 /// incorrectly.
+#[allow(unused)]    // Not yet implemented.
 pub const PASSWORD_ATTEMPTS: u32 = 3;
@@ -0,0 +1,304 @@
+use crate::util::secret::Secret;
+
+use std::{
+    fs::File,
+    path::{Path, PathBuf}
+};
+
+use std::io::{
+    self,
+    BufRead,
+    BufReader,
+    Seek,
+    SeekFrom
+};
+
+/// The length of a hexadecimal SHA-1 hash.
+const HASH_LEN: usize = 40;
+/// The length of the hash prefixes naming range files.
+const PREFIX_LEN: usize = 5;
+
+/// A local copy of the Have I Been Pwned password hashes.
+///
+/// Either a single file containing every hash ordered by hash, or a directory
+/// of range files each named after the first five characters of the hashes it
+/// contains, and containing the remaining characters. In both cases, each line
+/// consists of an uppercase hexadecimal SHA-1 hash (or its suffix) followed by
+/// a colon and the number of times it was seen in breaches.
+pub enum Breaches {
+    Ordered(File),
+    Ranges(PathBuf)
+}
+
+pub type Result<T> = io::Result<T>;
+
+impl Breaches {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+
+        if path.is_dir() {
+            Ok(Self::Ranges(path.to_owned()))
+        } else {
+            Ok(Self::Ordered(File::open(path)?))
+        }
+    }
+
+    /// Returns the number of times `pw` was seen in breaches, which is 0 if it
+    /// was never seen.
+    ///
+    /// The hash of `pw` is kept in a [`Secret`] buffer, as it may be used to
+    /// recover the password.
+    pub fn count(&mut self, pw: &str) -> Result<u64> {
+        let hash = sha1_hex(pw);
+
+        match self {
+            Self::Ordered(f) => search_ordered(f, &hash),
+            Self::Ranges(dir) => search_range(dir, &hash)
+        }
+    }
+}
+
+/// Searches the file `f` of hashes ordered by hash for `hash` using a binary
+/// search, without reading the whole file.
+fn search_ordered(f: &mut File, hash: &[u8]) -> Result<u64> {
+    let mut lo = 0;
+    let mut hi = f.metadata()?.len();
+    let mut line = Vec::new();
+
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let mut reader = BufReader::new(&mut *f);
+
+        // Find the first line starting at `mid` or after, by skipping to the
+        // end of the line containing the previous byte.
+        let mut start = mid.saturating_sub(1);
+        reader.seek(SeekFrom::Start(start))?;
+
+        if mid > 0 {
+            line.clear();
+            start += reader.read_until(b'\n', &mut line)? as u64;
+        }
+
+        // Skip blank lines, like a trailing one at the end of the file.
+        let mut end = start;
+
+        while end < hi {
+            start = end;
+            line.clear();
+
+            match reader.read_until(b'\n', &mut line)? {
+                0 => end = hi,
+                n => end += n as u64
+            }
+
+            if !is_blank(&line) {
+                break;
+            }
+        }
+
+        if start >= hi || is_blank(&line) {
+            hi = mid;
+            continue;
+        }
+
+        let Some((line_hash, count)) = split_line(&line) else {
+            return Err(invalid_data());
+        };
+
+        match line_hash.to_ascii_uppercase().as_slice().cmp(hash) {
+            std::cmp::Ordering::Equal => return Ok(count),
+            std::cmp::Ordering::Less => lo = end,
+            std::cmp::Ordering::Greater => hi = mid
+        }
+    }
+
+    Ok(0)
+}
+
+/// Searches the range file of `dir` which may contain `hash`, named after its
+/// prefix with or without a ".txt" extension.
+fn search_range(dir: &Path, hash: &[u8]) -> Result<u64> {
+    let (prefix, suffix) = hash.split_at(PREFIX_LEN);
+    // The prefix is hexadecimal, so valid UTF-8.
+    let prefix = std::str::from_utf8(prefix).unwrap();
+
+    let path = [prefix.to_owned(), format!("{prefix}.txt")].into_iter()
+        .map(|name| dir.join(name))
+        .find(|p| p.is_file());
+
+    let Some(path) = path else {
+        return Ok(0);
+    };
+
+    let reader = BufReader::new(File::open(path)?);
+
+    for line in reader.split(b'\n') {
+        let line = line?;
+
+        if is_blank(&line) {
+            continue;
+        }
+
+        let Some((line_suffix, count)) = split_line(&line) else {
+            return Err(invalid_data());
+        };
+
+        if line_suffix.eq_ignore_ascii_case(suffix) {
+            return Ok(count);
+        }
+    }
+
+    Ok(0)
+}
+
+/// Returns the uppercase hexadecimal SHA-1 hash of `pw`.
+fn sha1_hex(pw: &str) -> Secret<Vec<u8>> {
+    use sha1::{Sha1, Digest};
+
+    const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+    let digest = Secret::new(Sha1::digest(pw.as_bytes()).to_vec());
+    let mut result = Secret::new(Vec::with_capacity(HASH_LEN));
+
+    for b in digest.iter() {
+        result.push(DIGITS[(b >> 4) as usize]);
+        result.push(DIGITS[(b & 0xf) as usize]);
+    }
+
+    result
+}
+
+/// Verifies if `line` contains nothing but whitespace.
+fn is_blank(line: &[u8]) -> bool {
+    line.trim_ascii().is_empty()
+}
+
+/// Splits a line into its hash and count, ignoring trailing whitespace such as
+/// the carriage return of CRLF line endings.
+fn split_line(line: &[u8]) -> Option<(&[u8], u64)> {
+    let line = line.trim_ascii_end();
+    let sep = line.iter().position(|b| *b == b':')?;
+    let (hash, count) = (&line[..sep], &line[(sep + 1)..]);
+
+    let count = std::str::from_utf8(count).ok()?
+        .parse()
+        .ok()?;
+
+    Some((hash, count))
+}
+
+fn invalid_data() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid breach data format")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The passwords in the test data, each seen as many times as its index
+    /// plus one.
+    const SEEN: [&str; 6] = ["a", "b", "c", "d", "e", "password"];
+
+    /// Returns the test data as an ordered file, with lines ending in
+    /// `newline` and followed by `trailer`.
+    fn ordered_data(newline: &str, trailer: &str) -> (Vec<String>, String) {
+        let mut lines = SEEN.iter()
+            .enumerate()
+            .map(|(i, pw)| {
+                let hash = String::from_utf8(sha1_hex(pw).to_vec()).unwrap();
+                (hash, i + 1)
+            })
+            .collect::<Vec<_>>();
+
+        lines.sort();
+
+        let order = lines.iter()
+            .map(|(hash, _)| hash.clone())
+            .collect();
+        let data = lines.iter()
+            .map(|(hash, count)| format!("{hash}:{count}{newline}"))
+            .collect::<String>() + trailer;
+
+        (order, data)
+    }
+
+    /// Writes `data` to a new temporary file named after `name` and opens it.
+    fn open_ordered(name: &str, data: &str) -> Breaches {
+        let path = std::env::temp_dir()
+            .join(format!("pass-hibp-{}-{name}", std::process::id()));
+
+        std::fs::write(&path, data).unwrap();
+        let result = Breaches::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        result
+    }
+
+    /// Returns the password of `SEEN` whose hash is `hash`, and its count.
+    fn seen_with(hash: &str) -> (&'static str, u64) {
+        SEEN.iter()
+            .enumerate()
+            .find(|(_, pw)| *sha1_hex(pw) == hash.as_bytes())
+            .map(|(i, pw)| (*pw, i as u64 + 1))
+            .unwrap()
+    }
+
+    /// Verifies that every password of `SEEN` is found in `data`, along with
+    /// the first and last hashes, and that others are not.
+    fn check_ordered(name: &str, newline: &str, trailer: &str) {
+        let (order, data) = ordered_data(newline, trailer);
+        let mut breaches = open_ordered(name, &data);
+
+        for hash in [order.first().unwrap(), order.last().unwrap()] {
+            let (pw, count) = seen_with(hash);
+            assert_eq!(breaches.count(pw).unwrap(), count, "'{pw}'");
+        }
+
+        for (i, pw) in SEEN.iter().enumerate() {
+            assert_eq!(breaches.count(pw).unwrap(), i as u64 + 1, "'{pw}'");
+        }
+
+        for pw in ["", "f", "hunter2", "correct horse battery staple"] {
+            assert_eq!(breaches.count(pw).unwrap(), 0, "'{pw}'");
+        }
+    }
+
+    #[test]
+    fn ordered_finds_first_last_and_missing_hashes() {
+        check_ordered("lf", "\n", "");
+    }
+
+    #[test]
+    fn ordered_accepts_crlf() {
+        check_ordered("crlf", "\r\n", "");
+    }
+
+    #[test]
+    fn ordered_skips_blank_lines() {
+        check_ordered("blank", "\n", "\n\n");
+        check_ordered("blank-crlf", "\r\n", "\r\n");
+    }
+
+    #[test]
+    fn ranges_accept_crlf_and_blank_lines() {
+        let dir = std::env::temp_dir()
+            .join(format!("pass-hibp-{}-ranges", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let hash = sha1_hex("password");
+        let (prefix, suffix) = hash.split_at(PREFIX_LEN);
+        let prefix = std::str::from_utf8(prefix).unwrap();
+        let suffix = std::str::from_utf8(suffix).unwrap();
+
+        let data = format!("0000:1\r\n\r\n{suffix}:42\r\n");
+        std::fs::write(dir.join(format!("{prefix}.txt")), data).unwrap();
+
+        let mut breaches = Breaches::open(&dir).unwrap();
+        let counts = ["password", "hunter2"].map(|pw| breaches.count(pw));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(counts[0].as_ref().unwrap(), &42);
+        assert_eq!(counts[1].as_ref().unwrap(), &0);
+    }
+}
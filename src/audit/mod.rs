@@ -0,0 +1,129 @@
+pub mod strength;
+pub mod hibp;
+
+use strength::{Estimator, Estimate};
+use hibp::Breaches;
+
+use crate::config::DEFAULT_ITEM;
+
+use crate::find::{self, RecordPath};
+
+use crate::util::{
+    record::{Record, Item, Node},
+    secret::Secret
+};
+
+/// The length in bytes of the keys and hashes used to compare values.
+const HASH_LEN: usize = 32;
+
+/// Finds the items within `rec` which share their value with other items.
+///
+/// Only items named [`DEFAULT_ITEM`] are considered if `default_only` is true,
+/// and attachments are never considered.
+///
+/// Returns the paths of the items grouped by value, without the values. They
+/// are compared through hashes keyed with a random key unique to this call, so
+/// that neither the hashes nor the key reveal anything once the audit is done.
+pub fn find_reused(
+    rec: &Node<Record>,
+    default_only: bool
+) -> Vec<Vec<RecordPath>> {
+    let key = random_key();
+
+    let mut hashes = find::items_in(rec).into_iter()
+        .filter_map(|(path, item)| {
+            let item = item.borrow();
+
+            if default_only && item.name() != DEFAULT_ITEM {
+                return None;
+            }
+
+            let value = item.value().as_text()?;
+
+            Some((keyed_hash(&key, value.as_bytes()), path))
+        })
+        .collect::<Vec<_>>();
+
+    // Identical values are now adjacent.
+    hashes.sort_by(|(h1, _), (h2, _)| h1.as_slice().cmp(h2.as_slice()));
+
+    hashes.chunk_by(|(h1, _), (h2, _)| h1.as_slice() == h2.as_slice())
+        .filter(|group| group.len() > 1)
+        .map(|group| group.iter().map(|(_, p)| p.clone()).collect())
+        .collect()
+}
+
+/// Estimates the strength of each password within `rec`, that is each item
+/// named [`DEFAULT_ITEM`].
+///
+/// Returns the paths of the weak passwords with their estimated strength, in
+/// depth-first order. See [`Estimator::estimate`].
+pub fn find_weak(rec: &Node<Record>) -> Vec<(RecordPath, Estimate)> {
+    let estimator = Estimator::new();
+
+    password_items(rec)
+        .filter_map(|(path, item)| {
+            let item = item.borrow();
+            let estimate = estimator.estimate(item.value().as_text()?);
+
+            estimate.is_weak().then_some((path, estimate))
+        })
+        .collect()
+}
+
+/// Looks up each password within `rec`, that is each item named
+/// [`DEFAULT_ITEM`], in `breaches`.
+///
+/// Returns the paths of the passwords seen in breaches with the number of times
+/// they were seen, in depth-first order.
+pub fn find_breached(
+    rec: &Node<Record>,
+    breaches: &mut Breaches
+) -> hibp::Result<Vec<(RecordPath, u64)>> {
+    let mut result = Vec::new();
+
+    for (path, item) in password_items(rec) {
+        let item = item.borrow();
+
+        let Some(pw) = item.value().as_text() else {
+            continue;
+        };
+
+        match breaches.count(pw)? {
+            0 => continue,
+            n => result.push((path, n))
+        }
+    }
+
+    Ok(result)
+}
+
+/// Returns every item within `rec` named [`DEFAULT_ITEM`], with its full path.
+fn password_items(
+    rec: &Node<Record>
+) -> impl Iterator<Item = (RecordPath, Node<Item>)> {
+    find::items_in(rec).into_iter()
+        .filter(|(_, item)| item.borrow().name() == DEFAULT_ITEM)
+}
+
+/// Returns a cryptographically secure random key of [`HASH_LEN`] bytes.
+fn random_key() -> Secret<Vec<u8>> {
+    use rand::RngCore;
+    use rand::rngs::OsRng;
+
+    let mut result = Secret::new(vec![0_u8; HASH_LEN]);
+
+    // `OsRng` implements `CryptoRng` so it is cryptographically secure.
+    OsRng.fill_bytes(&mut result);
+    result
+}
+
+/// Returns the hash of `data` keyed with `key`, using the BLAKE2b algorithm.
+fn keyed_hash(key: &[u8], data: &[u8]) -> Secret<Vec<u8>> {
+    let hash = blake2b_simd::Params::new()
+        .hash_length(HASH_LEN)
+        .key(key)
+        .hash(data);
+
+    Secret::new(hash.as_bytes().to_vec())
+}
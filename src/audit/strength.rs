@@ -0,0 +1,858 @@
+use crate::config::MIN_PASSWORD_SCORE;
+
+use crate::util::secret::Secret;
+
+use std::collections::HashMap;
+
+use std::fmt;
+
+use std::fmt::Display;
+
+/// Common passwords, ordered by decreasing frequency.
+const PASSWORDS: &str = include_str!("dict/passwords.txt");
+/// The BIP-39 English word list, in no particular order.
+const ENGLISH: &str = include_str!("dict/english.txt");
+/// Common first names and surnames, in no particular order.
+const NAMES: &str = include_str!("dict/names.txt");
+
+/// Rows of a QWERTY keyboard, as typed without and with the shift key. Letters
+/// are lowercase since passwords are matched in lowercase.
+const KEYBOARD_ROWS: [&str; 5] = [
+    "`1234567890-=",
+    "~!@#$%^&*()_+",
+    "qwertyuiop[]\\",
+    "asdfghjkl;'",
+    "zxcvbnm,./"
+];
+
+/// The number of keys a keyboard pattern can start on.
+const KEYBOARD_STARTS: f64 = 47.0;
+/// The number of neighbours of each key within its row.
+const KEYBOARD_DEGREE: f64 = 2.0;
+
+/// The number of possibilities for a character not part of any pattern.
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+/// The minimum number of guesses for a pattern of a single character.
+const MIN_GUESSES_SINGLE_CHAR: f64 = 10.0;
+/// The minimum number of guesses for a pattern of several characters.
+const MIN_GUESSES_MULTI_CHAR: f64 = 50.0;
+/// Added for each pattern after the first, to account for the attacker trying
+/// shorter sequences of patterns first.
+const MIN_GUESSES_PER_PATTERN: f64 = 10_000.0;
+
+/// The minimum number of years an attacker tries when guessing a date.
+const MIN_YEAR_SPACE: f64 = 20.0;
+
+/// The minimum length of a dictionary word, in characters.
+const MIN_WORD_LEN: usize = 3;
+/// The maximum length of an analysed password, in characters. Any remaining
+/// characters are assumed to not be part of any pattern.
+const MAX_ANALYSED_LEN: usize = 100;
+
+/// Estimates the strength of passwords by searching them for patterns that
+/// attackers would try, in the manner of zxcvbn.
+///
+/// The dictionaries are embedded in the binary.
+pub struct Estimator {
+    /// The words of each dictionary with their rank, along with the pattern
+    /// they indicate.
+    dicts: [(Pattern, HashMap<&'static str, usize>); 3]
+}
+
+/// The estimated strength of a password.
+pub struct Estimate {
+    /// The base 10 logarithm of the number of guesses needed to find the
+    /// password.
+    guesses_log10: f64,
+    /// The patterns found in the password, without duplicates.
+    patterns: Vec<Pattern>
+}
+
+/// A weakness found in a password.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Pattern {
+    CommonPassword,
+    Word,
+    Name,
+    Keyboard,
+    Repeat,
+    Sequence,
+    Date
+}
+
+/// A part of a password matching a pattern.
+struct Match {
+    /// The index of the first character.
+    start: usize,
+    /// The index following the last character.
+    end: usize,
+    guesses_log10: f64,
+    pattern: Pattern
+}
+
+/// The best way found to guess a prefix of a password, ending with a match.
+#[derive(Clone, Copy)]
+struct Step {
+    /// The base 10 logarithm of the product of the guesses of each match.
+    product_log10: f64,
+    /// The base 10 logarithm of the total guesses.
+    guesses_log10: f64,
+    /// The index of the first character of the last match.
+    start: usize,
+    /// None if the last match is not a pattern.
+    pattern: Option<Pattern>
+}
+
+impl Estimator {
+    pub fn new() -> Self {
+        Self {
+            dicts: [
+                (Pattern::CommonPassword, ranked(PASSWORDS)),
+                (Pattern::Word, unranked(ENGLISH)),
+                (Pattern::Name, unranked(NAMES))
+            ]
+        }
+    }
+
+    /// Estimates the strength of `pw`.
+    ///
+    /// Copies of the password made during the analysis are kept in [`Secret`]
+    /// buffers. To prevent them from leaking, this should be called in a
+    /// context where memory is secured with [`proc::secure_mem`].
+    ///
+    /// [`proc::secure_mem`]: crate::util::proc::secure_mem
+    pub fn estimate(&self, pw: &str) -> Estimate {
+        // A password has at most one character per byte, so this is never
+        // reallocated (which would leave unerased copies behind).
+        let mut chars = Secret::new(Vec::with_capacity(pw.len()));
+        chars.extend(pw.chars());
+
+        let analysed_len = chars.len().min(MAX_ANALYSED_LEN);
+
+        let mut result = self.estimate_chars(&chars[..analysed_len]);
+
+        let remaining = (chars.len() - analysed_len) as f64;
+        result.guesses_log10 += remaining * BRUTEFORCE_CARDINALITY.log10();
+
+        result
+    }
+
+    fn estimate_chars(&self, chars: &[char]) -> Estimate {
+        // Only the first character of a lowercase mapping is kept, so that
+        // indices are the same in both slices.
+        let lower = Secret::new(
+            chars.iter()
+                .map(|c| c.to_lowercase().next().unwrap_or(*c))
+                .collect::<Vec<_>>()
+        );
+
+        let mut matches = Vec::new();
+
+        self.add_dictionary_matches(chars, &lower, &mut matches);
+        add_keyboard_matches(&lower, &mut matches);
+        self.add_repeat_matches(chars, &lower, &mut matches);
+        add_sequence_matches(&lower, &mut matches);
+        add_date_matches(&lower, &mut matches);
+
+        most_guessable(chars.len(), &matches)
+    }
+
+    fn add_dictionary_matches(
+        &self,
+        chars: &[char],
+        lower: &[char],
+        dest: &mut Vec<Match>
+    ) {
+        let len = lower.len();
+        // Pre-sized so that it is never reallocated, which would leave copies
+        // of its contents in memory.
+        let mut buf = Secret::new(String::with_capacity(len * 4));
+
+        for start in 0..len {
+            for end in (start + MIN_WORD_LEN)..=len {
+                let word = &lower[start..end];
+                let case_log10 = case_variations(&chars[start..end]).log10();
+
+                for (reversed, leet) in [(false, false), (true, false),
+                                         (false, true), (true, true)] {
+                    let subs = fill(&mut buf, word, reversed, leet);
+
+                    // The word is identical to one already tried.
+                    if leet && subs == 0 {
+                        continue;
+                    }
+
+                    for (pattern, dict) in &self.dicts {
+                        let Some(rank) = dict.get(buf.as_str()) else {
+                            continue;
+                        };
+
+                        let mut guesses_log10 = (*rank as f64).log10()
+                            + case_log10;
+
+                        if reversed {
+                            guesses_log10 += 2_f64.log10();
+                        }
+
+                        if leet {
+                            guesses_log10 += subs as f64 * 2_f64.log10();
+                        }
+
+                        dest.push(Match {
+                            start,
+                            end,
+                            guesses_log10,
+                            pattern: *pattern
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds repetitions of the same characters, like "aaa" or "abcabc".
+    fn add_repeat_matches(
+        &self,
+        chars: &[char],
+        lower: &[char],
+        dest: &mut Vec<Match>
+    ) {
+        let len = lower.len();
+
+        for start in 0..len {
+            for base_len in 1..=((len - start) / 2) {
+                let base = &lower[start..(start + base_len)];
+
+                // The repetition was already found from an earlier start, or
+                // with a shorter base.
+                let is_redundant = start >= base_len
+                    && lower[(start - base_len)..start] == *base
+                    || (1..base_len).any(|p| is_periodic(base, p));
+
+                if is_redundant {
+                    continue;
+                }
+
+                let mut count = 1;
+
+                while let Some(next) = lower.get(
+                    (start + count * base_len)..(start + (count + 1) * base_len)
+                ) {
+                    if next != base {
+                        break;
+                    }
+
+                    count += 1;
+                }
+
+                if count < 2 || count * base_len < 3 {
+                    continue;
+                }
+
+                let base_guesses_log10 = self
+                    .estimate_chars(&chars[start..(start + base_len)])
+                    .guesses_log10;
+
+                dest.push(Match {
+                    start,
+                    end: start + count * base_len,
+                    guesses_log10: base_guesses_log10 + (count as f64).log10(),
+                    pattern: Pattern::Repeat
+                });
+            }
+        }
+    }
+}
+
+impl Estimate {
+    /// Returns the estimate of a password known to have `entropy` bits, like a
+    /// generated one. No patterns are found in such a password.
+    pub fn from_entropy(entropy: f64) -> Self {
+        Self {
+            guesses_log10: entropy / std::f64::consts::LOG2_10,
+            patterns: Vec::new()
+        }
+    }
+
+    /// Returns a score of the password's strength from 0 (very weak) to 4
+    /// (strong).
+    pub fn score(&self) -> u8 {
+        match self.guesses_log10 {
+            g if g < 3.0  => 0,
+            g if g < 6.0  => 1,
+            g if g < 8.0  => 2,
+            g if g < 10.0 => 3,
+            _ => 4
+        }
+    }
+
+    /// Returns the estimated entropy of the password in bits.
+    pub fn entropy(&self) -> f64 {
+        self.guesses_log10 * std::f64::consts::LOG2_10
+    }
+
+    /// Verifies if the score is below [`MIN_PASSWORD_SCORE`].
+    pub fn is_weak(&self) -> bool {
+        self.score() < MIN_PASSWORD_SCORE
+    }
+
+    /// Returns the description of the score.
+    pub fn label(&self) -> &'static str {
+        match self.score() {
+            0 => "very weak",
+            1 => "weak",
+            2 => "fair",
+            3 => "good",
+            _ => "strong"
+        }
+    }
+
+    /// Returns the patterns found in the password. If there are none and the
+    /// password is weak, it is simply too short.
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.patterns
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Pattern::*;
+
+        match self {
+            CommonPassword => f.write_str("common password"),
+            Word           => f.write_str("dictionary word"),
+            Name           => f.write_str("common name"),
+            Keyboard       => f.write_str("keyboard pattern"),
+            Repeat         => f.write_str("repeated characters"),
+            Sequence       => f.write_str("character sequence"),
+            Date           => f.write_str("date")
+        }
+    }
+}
+
+/// Returns the words of `dict` with their rank, given by their position.
+fn ranked(dict: &'static str) -> HashMap<&'static str, usize> {
+    dict.lines()
+        .enumerate()
+        .map(|(i, w)| (w, i + 1))
+        .collect()
+}
+
+/// Returns the words of `dict`, all ranked as the size of the dictionary.
+fn unranked(dict: &'static str) -> HashMap<&'static str, usize> {
+    let size = dict.lines().count();
+
+    dict.lines()
+        .map(|w| (w, size))
+        .collect()
+}
+
+/// Writes `word` to `buf`, in reverse if `reversed` is true and with common
+/// substitutions of letters undone if `leet` is true.
+///
+/// Returns the number of substitutions undone.
+fn fill(buf: &mut String, word: &[char], reversed: bool, leet: bool) -> usize {
+    let mut subs = 0;
+
+    buf.clear();
+
+    let mut push = |c: char| match unleet(c).filter(|_| leet) {
+        Some(l) => {
+            subs += 1;
+            buf.push(l);
+        }
+
+        None => buf.push(c)
+    };
+
+    if reversed {
+        word.iter().rev().for_each(|c| push(*c));
+    } else {
+        word.iter().for_each(|c| push(*c));
+    }
+
+    subs
+}
+
+/// Returns the letter that `c` commonly substitutes, if any.
+fn unleet(c: char) -> Option<char> {
+    Some(match c {
+        '4' | '@'             => 'a',
+        '8'                   => 'b',
+        '(' | '{' | '[' | '<' => 'c',
+        '3'                   => 'e',
+        '6' | '9'             => 'g',
+        '1' | '!' | '|'       => 'i',
+        '0'                   => 'o',
+        '$' | '5'             => 's',
+        '7' | '+'             => 't',
+        '%'                   => 'x',
+        '2'                   => 'z',
+         _                    => return None
+    })
+}
+
+/// Returns the number of ways `word` may be capitalised, given how it is.
+fn case_variations(word: &[char]) -> f64 {
+    let upper = word.iter().filter(|c| c.is_uppercase()).count();
+    let lower = word.iter().filter(|c| c.is_lowercase()).count();
+
+    let first_only = upper == 1
+        && word.first().is_some_and(|c| c.is_uppercase());
+    let last_only = upper == 1
+        && word.last().is_some_and(|c| c.is_uppercase());
+
+    if upper == 0 {
+        1.0
+    } else if lower == 0 || first_only || last_only {
+        2.0
+    } else {
+        (1..=upper.min(lower))
+            .map(|i| binomial(upper + lower, i))
+            .sum()
+    }
+}
+
+/// Adds runs of adjacent keys in a keyboard row, like "qwerty" or "asdsa".
+fn add_keyboard_matches(lower: &[char], dest: &mut Vec<Match>) {
+    let pos = |c: char| KEYBOARD_ROWS.iter()
+        .enumerate()
+        .find_map(|(row, keys)| {
+            Some((row, keys.chars().position(|k| k == c)?))
+        });
+
+    let mut start = 0;
+
+    while start < lower.len() {
+        let mut end = start + 1;
+        let mut turns = 0;
+        let mut direction = 0_isize;
+
+        while let (Some(prev), Some(next)) = (
+            lower.get(end - 1).and_then(|c| pos(*c)),
+            lower.get(end).and_then(|c| pos(*c))
+        ) {
+            let step = next.1 as isize - prev.1 as isize;
+
+            if prev.0 != next.0 || step.abs() != 1 {
+                break;
+            }
+
+            if step != direction {
+                turns += 1;
+                direction = step;
+            }
+
+            end += 1;
+        }
+
+        if end - start >= 3 {
+            dest.push(Match {
+                start,
+                end,
+                guesses_log10: keyboard_guesses(end - start, turns).log10(),
+                pattern: Pattern::Keyboard
+            });
+
+            start = end - 1;
+        } else {
+            start += 1;
+        }
+    }
+}
+
+/// Returns the number of keyboard patterns of `len` keys with up to `turns`
+/// changes of direction.
+fn keyboard_guesses(len: usize, turns: usize) -> f64 {
+    let mut result = 0.0;
+
+    for i in 2..=len {
+        for j in 1..=turns.min(i - 1) {
+            result += binomial(i - 1, j - 1)
+                * KEYBOARD_STARTS
+                * KEYBOARD_DEGREE.powi(j as i32);
+        }
+    }
+
+    result
+}
+
+/// Adds runs of characters separated by a constant interval in the alphabet or
+/// in digits, like "abcd" or "9753".
+fn add_sequence_matches(lower: &[char], dest: &mut Vec<Match>) {
+    const MAX_INTERVAL: i64 = 5;
+
+    let class = |c: char| if c.is_ascii_lowercase() {
+        Some(26.0)
+    } else if c.is_ascii_digit() {
+        Some(10.0)
+    } else {
+        None
+    };
+
+    let mut start = 0;
+
+    while start + 1 < lower.len() {
+        let first = lower[start];
+        let interval = lower[start + 1] as i64 - first as i64;
+        let mut end = start + 1;
+
+        while end < lower.len()
+            && class(lower[end]).is_some()
+            && class(lower[end]) == class(first)
+            && lower[end] as i64 - lower[end - 1] as i64 == interval
+        {
+            end += 1;
+        }
+
+        let is_sequence = interval != 0
+            && interval.abs() <= MAX_INTERVAL
+            && class(first).is_some();
+
+        if is_sequence && end - start >= 3 {
+            let mut base = match first {
+                'a' | 'z' | '0' | '1' | '9' => 4.0,
+                c => class(c).unwrap_or(BRUTEFORCE_CARDINALITY)
+            };
+
+            if interval < 0 {
+                base *= 2.0;
+            }
+
+            dest.push(Match {
+                start,
+                end,
+                guesses_log10: (base * (end - start) as f64).log10(),
+                pattern: Pattern::Sequence
+            });
+
+            start = end - 1;
+        } else {
+            start += 1;
+        }
+    }
+}
+
+/// Adds years and dates with or without separators, like "1987", "12/3/99" or
+/// "20011231".
+fn add_date_matches(lower: &[char], dest: &mut Vec<Match>) {
+    // The possible positions of separators in dates without them.
+    const SPLITS: [&[(usize, usize)]; 5] = [
+        &[(1, 2), (2, 3)],
+        &[(1, 3), (2, 3)],
+        &[(1, 2), (2, 4), (4, 5)],
+        &[(1, 3), (2, 3), (4, 5), (4, 6)],
+        &[(2, 4), (4, 6)]
+    ];
+
+    let year_guesses = |year: i64| {
+        ((year - current_year()).abs() as f64).max(MIN_YEAR_SPACE)
+    };
+
+    for start in 0..lower.len() {
+        for end in (start + 4)..=lower.len().min(start + 10) {
+            let s = &lower[start..end];
+
+            let mut guesses = Option::<f64>::None;
+            let mut add = |g: f64| {
+                guesses = Some(guesses.map_or(g, |old| old.min(g)));
+            };
+
+            if s.iter().all(char::is_ascii_digit) {
+                if s.len() == 4 {
+                    let year = to_int(s);
+
+                    if (1900..=2099).contains(&year) {
+                        add(year_guesses(year));
+                    }
+                }
+
+                for (i, j) in SPLITS.get(s.len() - 4).copied().unwrap_or(&[]) {
+                    let parts = [&s[..*i], &s[*i..*j], &s[*j..]];
+
+                    if let Some(year) = date_year(parts) {
+                        add(year_guesses(year) * 365.0);
+                    }
+                }
+            } else if let Some(parts) = split_date(s) {
+                if let Some(year) = date_year(parts) {
+                    add(year_guesses(year) * 365.0 * 4.0);
+                }
+            }
+
+            if let Some(g) = guesses {
+                dest.push(Match {
+                    start,
+                    end,
+                    guesses_log10: g.log10(),
+                    pattern: Pattern::Date
+                });
+            }
+        }
+    }
+}
+
+/// Splits `s` into three groups of digits separated by the same separator.
+fn split_date(s: &[char]) -> Option<[&[char]; 3]> {
+    const SEPARATORS: &[char] = &[' ', '-', '/', '\\', '_', '.'];
+
+    let sep = *s.iter().find(|c| !c.is_ascii_digit())?;
+
+    if !SEPARATORS.contains(&sep) {
+        return None;
+    }
+
+    let mut parts = s.split(|c| *c == sep);
+    let result = [parts.next()?, parts.next()?, parts.next()?];
+
+    let is_valid = parts.next().is_none() && result.iter()
+        .all(|p| {
+            (1..=4).contains(&p.len()) && p.iter().all(char::is_ascii_digit)
+        });
+
+    is_valid.then_some(result)
+}
+
+/// Returns the full year of the date made of `parts` in any common order, if
+/// it is valid.
+fn date_year(parts: [&[char]; 3]) -> Option<i64> {
+    let is_day_month = |d: &[char], m: &[char]| {
+        d.len() <= 2 && m.len() <= 2
+            && (1..=31).contains(&to_int(d))
+            && (1..=12).contains(&to_int(m))
+    };
+
+    let year = |y: &[char]| match (y.len(), to_int(y)) {
+        (4, y) if (1000..=2099).contains(&y) => Some(y),
+        (2, y) if y > 50 => Some(1900 + y),
+        (2, y) => Some(2000 + y),
+        _ => None
+    };
+
+    let [a, b, c] = parts;
+
+    if is_day_month(a, b) || is_day_month(b, a) {
+        if let Some(y) = year(c) {
+            return Some(y);
+        }
+    }
+
+    if is_day_month(b, c) || is_day_month(c, b) {
+        return year(a);
+    }
+
+    None
+}
+
+/// Returns the minimum number of guesses needed to find a password of `len`
+/// characters made of the patterns in `matches` and of arbitrary characters.
+///
+/// Based on the search for the most guessable match sequence used by zxcvbn.
+fn most_guessable(len: usize, matches: &[Match]) -> Estimate {
+    // The best step ending at each index, for each number of matches.
+    let mut best = vec![vec![Option::<Step>::None; len + 1]; len + 1];
+
+    // The base 10 logarithm of the factorial of each possible match count.
+    let factorial_log10 = (0..=len)
+        .scan(0.0, |acc, i| {
+            *acc += (i.max(1) as f64).log10();
+            Some(*acc)
+        })
+        .collect::<Vec<f64>>();
+
+    best[0][0] = Some(Step {
+        product_log10: 0.0,
+        guesses_log10: 0.0,
+        start: 0,
+        pattern: None
+    });
+
+    // Arbitrary characters between `start` and `end`.
+    let bruteforce = |start: usize, end: usize| {
+        let min = if end - start == 1 {
+            MIN_GUESSES_SINGLE_CHAR + 1.0
+        } else {
+            MIN_GUESSES_MULTI_CHAR + 1.0
+        };
+
+        let guesses_log10 =
+            (end - start) as f64 * BRUTEFORCE_CARDINALITY.log10();
+
+        (start, guesses_log10.max(min.log10()), None)
+    };
+
+    for end in 1..=len {
+        let candidates = matches.iter()
+            .filter(|m| m.end == end)
+            .map(|m| {
+                let min = if m.end - m.start == 1 {
+                    MIN_GUESSES_SINGLE_CHAR
+                } else {
+                    MIN_GUESSES_MULTI_CHAR
+                };
+
+                (m.start, m.guesses_log10.max(min.log10()), Some(m.pattern))
+            })
+            .chain((0..end).map(|start| bruteforce(start, end)))
+            .collect::<Vec<_>>();
+
+        for (start, match_log10, pattern) in candidates {
+            for count in 0..end {
+                let Some(prev) = best[start][count] else {
+                    continue;
+                };
+
+                // A sequence of arbitrary characters is always guessed whole.
+                if pattern.is_none() && prev.pattern.is_none() && start > 0 {
+                    continue;
+                }
+
+                let count = count + 1;
+                let product_log10 = prev.product_log10 + match_log10;
+                let guesses_log10 = log10_add(
+                    factorial_log10[count] + product_log10,
+                    (count - 1) as f64 * MIN_GUESSES_PER_PATTERN.log10()
+                );
+
+                let is_better = best[end][count]
+                    .is_none_or(|s| guesses_log10 < s.guesses_log10);
+
+                if is_better {
+                    best[end][count] = Some(Step {
+                        product_log10,
+                        guesses_log10,
+                        start,
+                        pattern
+                    });
+                }
+            }
+        }
+    }
+
+    let Some((mut count, step)) = best[len].iter()
+        .enumerate()
+        .filter_map(|(i, s)| Some((i, (*s)?)))
+        .min_by(|(_, a), (_, b)| a.guesses_log10.total_cmp(&b.guesses_log10))
+    else {
+        return Estimate { guesses_log10: 0.0, patterns: Vec::new() };
+    };
+
+    let mut patterns = Vec::new();
+    let mut end = len;
+
+    while end > 0 {
+        // Every step on the path to the best step exists.
+        let s = best[end][count].unwrap();
+
+        patterns.extend(s.pattern);
+        end = s.start;
+        count -= 1;
+    }
+
+    patterns.sort();
+    patterns.dedup();
+
+    Estimate { guesses_log10: step.guesses_log10, patterns }
+}
+
+/// Returns the base 10 logarithm of the sum of the numbers whose base 10
+/// logarithms are `a` and `b`.
+fn log10_add(a: f64, b: f64) -> f64 {
+    let (max, min) = if a > b { (a, b) } else { (b, a) };
+
+    max + (1.0 + 10_f64.powf(min - max)).log10()
+}
+
+/// Verifies if `s` consists of its first `period` characters repeated.
+fn is_periodic(s: &[char], period: usize) -> bool {
+    s.len().is_multiple_of(period)
+        && s.chunks(period).all(|c| c == &s[..period])
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (1..=k).fold(1.0, |acc, i| acc * (n + 1 - i) as f64 / i as f64)
+}
+
+/// Returns the value of `digits` as an integer.
+fn to_int(digits: &[char]) -> i64 {
+    digits.iter()
+        .filter_map(|c| c.to_digit(10))
+        .fold(0, |acc, d| acc * 10 + d as i64)
+}
+
+fn current_year() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const SECS_PER_YEAR: u64 = 31_556_952;
+
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+
+    1970 + (secs / SECS_PER_YEAR) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Pattern::*;
+
+    /// Verifies that `pw` has the given score and patterns.
+    fn check(pw: &str, score: u8, patterns: &[Pattern]) {
+        let estimate = Estimator::new().estimate(pw);
+
+        assert_eq!(estimate.score(), score, "score of '{pw}'");
+        assert!(estimate.patterns() == patterns, "patterns of '{pw}'");
+    }
+
+    #[test]
+    fn common_password() {
+        check("password", 0, &[CommonPassword]);
+    }
+
+    #[test]
+    fn dictionary_word() {
+        check("abandon", 1, &[Word]);
+        check("4b4nd0n", 1, &[Word]);
+        check("nodnaba", 1, &[Word]);
+        check("n0dn4b4", 1, &[Word]);
+    }
+
+    #[test]
+    fn dictionary_variants_cost_more_guesses() {
+        let estimator = Estimator::new();
+        let guesses = |pw| estimator.estimate(pw).guesses_log10;
+
+        assert!(guesses("4b4nd0n") > guesses("abandon"));
+        assert!(guesses("nodnaba") > guesses("abandon"));
+    }
+
+    #[test]
+    fn keyboard_walk() {
+        check("sdfghjk", 0, &[Keyboard]);
+        check("lkjhgfd", 0, &[Keyboard]);
+    }
+
+    #[test]
+    fn repeat() {
+        check("aaaaaaaa", 0, &[Repeat]);
+        check("xyzxyzxyz", 0, &[Repeat]);
+    }
+
+    #[test]
+    fn sequence() {
+        check("mnopqrst", 0, &[Sequence]);
+        check("13579", 0, &[Sequence]);
+    }
+
+    #[test]
+    fn date() {
+        check("13/05/1987", 1, &[Date]);
+        check("19870513", 1, &[Date]);
+    }
+
+    #[test]
+    fn long_random_string() {
+        check("tX9#qL2$vR7!mZ4&wK", 4, &[]);
+    }
+}
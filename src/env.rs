@@ -1,5 +1,7 @@
 use crate::config;
 use crate::tui;
+use crate::gen;
+use crate::output;
 
 use crate::find::{
     RecordPath,
@@ -8,7 +10,8 @@ use crate::find::{
 
 use crate::util::{
     xdg_path,
-    file
+    file,
+    clip
 };
 
 use crate::util::file::SafePath;
@@ -22,7 +25,8 @@ use std::fmt::Display;
 
 use std::{
     path::{Path, PathBuf},
-    time::Duration
+    time::Duration,
+    num::NonZeroUsize
 };
 
 /// Commonly used data structures relevant to the environment, and useful for
@@ -30,13 +34,20 @@ use std::{
 pub mod prelude {
     pub use super::{
         Cmd, FileCmd,
-        ReadCmd, ChangeCmd, CreateCmd
+        ReadCmd, ChangeCmd, CreateCmd,
+        NewPassword
     };
 }
 
 /// Program binary name.
 pub const PROGNAME: &str = env!("CARGO_BIN_NAME");
 
+/// The hidden long option starting the program as a clipboard holder process,
+/// which accepts the clipboard options. See [`output::clip_timed`].
+///
+/// [`output::clip_timed`]: crate::output::clip_timed
+pub const CLIP_HOLDER_OPT: &str = "clip-holder";
+
 /// The command to be executed.
 // TODO: maybe find way to improve such that there is no impurity (creating dirs)
 //  maybe shouldnt even create safepath: only Option<PathBuf> for user provided
@@ -44,6 +55,16 @@ pub const PROGNAME: &str = env!("CARGO_BIN_NAME");
 pub enum Cmd {
     ShowUsage(Usage),
     ShowVersion(Version),
+    /// Displaying a random password, without handling a pass file.
+    Generate(gen::Policy),
+    /// Displaying a random passphrase and its entropy, without handling a pass
+    /// file.
+    GeneratePhrase(gen::Passphrase),
+    /// Stopping the processes holding items in the clipboard, which clear it.
+    ClearClip,
+    /// Holding the texts received on standard input in the clipboard, as a
+    /// clipboard holder process.
+    HoldClip(clip::HoldOpts),
     HandleFile(FileCmd, SafePath)
 }
 
@@ -57,15 +78,47 @@ pub enum FileCmd {
 /// Reading data from a pass file.
 pub enum ReadCmd {
     /// Displaying an item.
-    Show(Vec<RecordPath>, MatchKind),
-    /// Copying an item to the clipboard, and keeping it there for a `Duration`.
-    Clip(RecordPath, MatchKind, Duration),
+    ///
+    /// For the following four commands, the last flag determines whether the
+    /// resolved paths of targets are reported, like `report`. The commands with
+    /// an [`output::Format`] print in that format.
+    Show {
+        paths: Vec<RecordPath>,
+        mk: MatchKind,
+        /// Whether attachments are displayed.
+        attachments: bool,
+        /// The time after which the item is erased from the terminal, if any.
+        time: Option<Duration>,
+        /// Whether the item is displayed outside a terminal without
+        /// restrictions.
+        raw: bool,
+        format: output::Format,
+        report: bool
+    },
+    /// Copying an item to the clipboard, and keeping it there as specified.
+    /// The items of the target group to copy in turn may be specified.
+    Clip(RecordPath, MatchKind, Vec<String>, clip::HoldOpts, bool),
+    /// Displaying an item as a QR code on the terminal for some time.
+    Qr(RecordPath, MatchKind, Duration, bool),
     /// Displaying the names of a group's records, or of an item.
-    List(Option<Vec<RecordPath>>, MatchKind),
+    List(Option<Vec<RecordPath>>, MatchKind, output::Format, bool),
     /// Displaying a tree representation of a group, or an item. Only the names
     /// of the records are shown, and their layout. If no target is provided,
     /// the root group is considered the target.
-    Tree(Option<Vec<RecordPath>>, MatchKind),
+    Tree(Option<Vec<RecordPath>>, MatchKind, output::Format, bool),
+    /// Displaying the paths of records matching a pattern. Item values are
+    /// also matched if the flag is set.
+    Search(String, MatchKind, bool, output::Format),
+    /// Writing an item's value to a new file.
+    Extract(RecordPath, MatchKind, PathBuf),
+    /// Displaying the paths of items sharing the same value. Only items named
+    /// [`config::DEFAULT_ITEM`] are considered if the flag is set.
+    AuditReuse(bool, output::Format),
+    /// Displaying the paths of weak passwords, with the reasons they are weak.
+    AuditStrength(output::Format),
+    /// Displaying the paths of passwords found in a local copy of breached
+    /// password hashes, with the number of times they were seen.
+    AuditBreached(PathBuf, output::Format),
     /// Displaying a serial representation of the data.
     Export
 }
@@ -75,16 +128,27 @@ pub enum ChangeCmd {
     /// Modifying the data.
     Modify(tui::Config),
     /// Changing the password used to access the data.
-    ChangePassword
+    ChangePassword(NewPassword)
 }
 
 /// Creating a new pass file.
 pub enum CreateCmd {
     /// Creating a pass file with from input data in serial form.
-    Import,
+    Import(NewPassword),
     /// Creating a pass file with no data, and with specified name for root
     /// group.
-    CreateEmpty(String)
+    CreateEmpty(String, NewPassword)
+}
+
+/// How the password of a new pass file, or the new password of a pass file, is
+/// chosen.
+#[derive(Clone, Copy)]
+pub struct NewPassword {
+    /// A passphrase to generate instead of reading user input.
+    pub phrase: Option<gen::Passphrase>,
+    /// Whether to accept passwords weaker than
+    /// [`config::MIN_MASTER_PASSWORD_SCORE`].
+    pub allow_weak: bool
 }
 
 pub struct Usage;
@@ -110,7 +174,7 @@ impl Cmd {
 
         use lexopt::prelude::*;
         use lexopt::Parser;
-        use lexopt::Error::Custom;
+        use lexopt::Error::{Custom, UnexpectedArgument};
 
         let mut parser = Parser::from_env();
         let mut opts = FileCmdOpts::default();
@@ -122,18 +186,109 @@ impl Cmd {
 
             match arg {
                 Short('c') | Long("clip") => cmd = Clip,
+                Short('Q') | Long("qr") => cmd = Qr,
                 Short('l') | Long("list") => cmd = List,
                 Short('t') | Long("tree") => cmd = Tree,
 
+                Short('s') | Long("search") => {
+                    opts.search_pat = parser.value()?.parse()?;
+                    cmd = Search;
+                }
+
+                Short('x') | Long("extract") => {
+                    opts.extract_path = parser.value()?.into();
+                    cmd = Extract;
+                }
+
                 Short('e') | Long("exact") =>
                     opts.match_kind = MatchKind::Exact,
-                Short('d') | Long("duration") =>
-                    opts.clip_time = parser.value()?.parse()?,
+                Short('F') | Long("flat") =>
+                    opts.match_kind = MatchKind::Flat,
+                Short('p') | Long("prefix") =>
+                    opts.match_kind = MatchKind::Prefix,
+                Short('S') | Long("substring") =>
+                    opts.match_kind = MatchKind::Substring,
+                Short('i') | Long("smart-case") =>
+                    opts.match_kind = MatchKind::SmartCase,
+                Short('a') | Long("attachments") =>
+                    opts.show_attachments = true,
+                Short('r') | Long("report") =>
+                    opts.report_path = Some(true),
+                Short('q') | Long("quiet") =>
+                    opts.report_path = Some(false),
+                Short('V') | Long("values") =>
+                    opts.search_values = true,
+                Short('d') | Long("duration") => opts.clip.time =
+                    Duration::from_secs(parser.value()?.parse()?),
+                Short('b') | Long("selection") => {
+                    let val = parser.value()?.string()?;
+
+                    opts.clip.selection = clip::Selection::from_str(&val)
+                        .ok_or_else(|| Custom(
+                            format!("invalid selection '{val}'").into()
+                        ))?;
+                }
+                Short('K') | Long("restore") =>
+                    opts.clip.restore = true,
+                // 0 for unlimited pastes, as in the editing interface.
+                Short('N') | Long("pastes") => opts.clip.pastes =
+                    NonZeroUsize::new(parser.value()?.parse()?),
+                Short('O') | Long("osc52") =>
+                    opts.clip.terminal = true,
+                Short('T') | Long("timed") =>
+                    opts.timed_show = true,
+                Short('z') | Long("raw") =>
+                    opts.raw_show = true,
+                Short('J') | Long("json") =>
+                    opts.format = output::Format::Json,
+                Short('X') | Long("clear-clip") => cmd = FileCmdVerb::ClearClip,
+                Long(CLIP_HOLDER_OPT) => cmd = FileCmdVerb::HoldClip,
+                Short('u') | Long("fields") => {
+                    let val = parser.value()?.string()?;
+                    opts.clip_fields = val.split(',')
+                        .map(String::from)
+                        .collect();
+                }
                 Short('f') | Long("file") =>
                     file_path = Some(parser.value()?.into()),
 
+                Short('R') | Long("audit-reuse") => cmd = AuditReuse,
+                Short('W') | Long("audit-strength") => cmd = AuditStrength,
+                Short('B') | Long("audit-breached") => {
+                    opts.breaches_path = parser.value()?.into();
+                    cmd = AuditBreached;
+                }
+                Short('D') | Long("default-only") =>
+                    opts.default_only = true,
+
+                Short('g') | Long("generate") => cmd = FileCmdVerb::Generate,
+                Short('L') | Long("length") =>
+                    opts.gen_policy.length = parser.value()?.parse()?,
+                Short('k') | Long("classes") => {
+                    let val = parser.value()?.string()?;
+
+                    opts.gen_policy.classes = gen::Classes::from_str(&val)
+                        .ok_or_else(|| Custom(
+                            format!("invalid character classes '{val}'").into()
+                        ))?;
+                }
+                Short('A') | Long("unambiguous") =>
+                    opts.gen_policy.ambiguous = false,
+                Short('w') | Long("words") => {
+                    opts.passphrase.words = parser.value()?.parse()?;
+                    opts.use_passphrase = true;
+                }
+                Short('j') | Long("separator") =>
+                    opts.passphrase.separator = parser.value()?.parse()?,
+                Short('U') | Long("capitalise") =>
+                    opts.passphrase.capitalise = true,
+                Short('n') | Long("digit") =>
+                    opts.passphrase.digit = true,
+
                 Short('M') | Long("modify")    => cmd = Edit,
                 Short('P') | Long("change-pw") => cmd = ChangePassword,
+                Short('o') | Long("allow-weak") =>
+                    opts.allow_weak = true,
 
                 Short('E') | Long("export") => cmd = Export,
                 Short('I') | Long("import") => cmd = Import,
@@ -157,6 +312,24 @@ impl Cmd {
             }
         }
 
+        // Some commands do not involve any pass file.
+        if let FileCmdVerb::Generate
+            | FileCmdVerb::ClearClip
+            | FileCmdVerb::HoldClip = cmd
+        {
+            if let Some(arg) = opts.record_paths_raw.into_iter().next() {
+                return Err(UnexpectedArgument(arg.into()).into());
+            }
+
+            return Ok(match cmd {
+                FileCmdVerb::ClearClip => Cmd::ClearClip,
+                FileCmdVerb::HoldClip => Cmd::HoldClip(opts.clip),
+                _ if opts.use_passphrase =>
+                    Cmd::GeneratePhrase(opts.passphrase),
+                _ => Cmd::Generate(opts.gen_policy)
+            });
+        }
+
         let file_cmd = FileCmd::from_parts(cmd, opts)?;
 
         let data_dir = xdg_path::data_dir(PROGNAME)?;
@@ -177,16 +350,66 @@ impl Display for Usage {
 Usage: {} [OPTION...] [TARGET...]
 Securely manage hierarchical data.
 
-  -c, --clip        copy target item to primary clipboard instead of displaying
+  -c, --clip        copy target item to the clipboard instead of displaying
+  -Q, --qr          display target item as a QR code, erased after the duration
+                    or a keypress
   -l, --list        list the target's contents (root if not specified)
   -t, --tree        display a tree of the target (root if not specified)
+  -s, --search      list the paths of records matching the specified pattern
+  -x, --extract     write target item to the specified new file
+  -R, --audit-reuse list groups of items sharing the same value
+  -W, --audit-strength
+                    list weak passwords and the reasons they are weak
+  -B, --audit-breached
+                    list passwords found in the specified local HIBP dump
+  -g, --generate    display a random password (no pass file involved)
+  -X, --clear-clip  clear the clipboard of items copied by '-c' (no pass file
+                    involved)
 
   -e, --exact       find exact match of target (default: fuzzy match)
-  -d, --duration    time in seconds to keep target in clipboard (default: {})
+  -F, --flat        fuzzy match target against full record paths
+  -p, --prefix      match beginning of target names (smart case)
+  -S, --substring   match any part of target names (smart case)
+  -i, --smart-case  find exact match of target, ignoring case if lowercase
+  -a, --attachments display target attachments as raw data
+  -r, --report      report resolved target paths (default on a terminal)
+  -q, --quiet       do not report resolved target paths
+  -V, --values      also match item values when searching (never displayed)
+  -D, --default-only
+                    only audit items named '{}'
+  -d, --duration    time in seconds to keep target in clipboard or on screen
+                    (default: {})
+  -b, --selection   clipboard selection to copy to: 'primary', 'clipboard' or
+                    'both' (default: {})
+  -K, --restore     restore the previous clipboard contents afterwards
+  -N, --pastes      clear the clipboard after target was pasted the specified
+                    number of times (0 for unlimited), or when the duration
+                    expires
+  -O, --osc52       copy through the terminal with OSC 52 escape sequences (the
+                    default without a display, as over SSH)
+  -u, --fields      copy the specified comma-separated items of the target group
+                    in turn, each after the previous one was pasted (or Enter
+                    was pressed)
+  -T, --timed       erase displayed target from the terminal after the duration
+                    or a keypress
+  -z, --raw         display target even if stdout is not a terminal, without
+                    warning
+  -J, --json        print shown, listed, searched and audited records as JSON,
+                    with full paths and kinds
   -f, --file        specify a pass file (default: standard data file)
-
-  -M, --modify      launch editing interface (respects '-e' and '-d')
+  -L, --length      length of generated passwords (default: {})
+  -k, --classes     character classes of generated passwords (default: luds)
+  -A, --unambiguous exclude easily confused characters from generated passwords
+  -w, --words       generate passphrases with the specified number of words
+                    instead, also as the new password of pass files
+  -j, --separator   character between passphrase words (default: '-')
+  -U, --capitalise  capitalise passphrase words
+  -n, --digit       append a digit to a random passphrase word
+
+  -M, --modify      launch editing interface (respects match kind, clipboard,
+                    '-r', '-q' and generation options)
   -P, --change-pw   change the pass file's password
+  -o, --allow-weak  accept a weak password for the pass file (not recommended)
 
   -E, --export      output data in serial form
   -I, --import      create a pass file from serial data (read from stdin)
@@ -197,11 +420,19 @@ Securely manage hierarchical data.
 
 Note: By default, the target item is printed to standard output.
       Targets are passed as dot-separated record paths.
+      Path elements may be glob patterns ('*', '?', and '**' for any depth).
+      Dots and wildcards in record names are escaped with a backslash ('\\.').
+      Smart case ignores case unless the target contains uppercase letters.
+      Character classes are 'l' (lowercase), 'u' (uppercase), 'd' (digits)
+      and 's' (symbols).
       Passing a group as a target item implies its child item '{}'.
 
 Example: pass -d5 -c foo.bar",
             PROGNAME,
+            config::DEFAULT_ITEM,
             config::DEFAULT_CLIP_TIME,
+            config::DEFAULT_CLIP_SELECTION,
+            config::DEFAULT_GEN_LENGTH,
             config::DEFAULT_ITEM
         )
     }
@@ -251,11 +482,32 @@ impl From<xdg_path::Error> for Error {
 }
 
 /// Options relevant to handling a file.
+#[derive(Default)]
 struct FileCmdOpts {
-    /// The path of the target `Record`.
+    /// The path of the target `Record`, root group by default.
     record_paths_raw: Vec<String>,
     match_kind: MatchKind,
-    clip_time: u64,
+    clip: clip::HoldOpts,
+    clip_fields: Vec<String>,
+    /// Whether to erase displayed items once the clipboard duration runs out.
+    timed_show: bool,
+    /// Whether to display items outside a terminal without restrictions.
+    raw_show: bool,
+    format: output::Format,
+    /// Whether to report resolved target paths, or `None` to do so only if
+    /// standard error is a terminal.
+    report_path: Option<bool>,
+    show_attachments: bool,
+    search_pat: String,
+    search_values: bool,
+    extract_path: PathBuf,
+    default_only: bool,
+    breaches_path: PathBuf,
+    gen_policy: gen::Policy,
+    passphrase: gen::Passphrase,
+    /// Whether to generate passphrases instead of passwords where applicable.
+    use_passphrase: bool,
+    allow_weak: bool,
     root_name: String
 }
 
@@ -265,8 +517,17 @@ enum FileCmdVerb {
     #[default]
     Show,
     Clip,
+    Qr,
     List,
     Tree,
+    Search,
+    Extract,
+    AuditReuse,
+    AuditStrength,
+    AuditBreached,
+    Generate,
+    ClearClip,
+    HoldClip,
 
     Edit,
     ChangePassword,
@@ -282,37 +543,68 @@ impl FileCmd {
         use FileCmdVerb::*;
 
         use tui::Config;
-        use lexopt::Error::{MissingValue, UnexpectedArgument};
+        use lexopt::Error::{Custom, MissingValue, UnexpectedArgument};
+
+        use io::IsTerminal;
 
         let FileCmdOpts {
             record_paths_raw: rec_paths_raw,
             match_kind,
-            clip_time,
+            clip,
+            clip_fields,
+            timed_show,
+            raw_show,
+            format,
+            report_path,
+            show_attachments,
+            search_pat,
+            search_values,
+            extract_path,
+            default_only,
+            breaches_path,
+            gen_policy,
+            passphrase,
+            use_passphrase,
+            allow_weak,
             root_name
         } = opts;
 
-        let clip_time = Duration::from_secs(clip_time);
+        let new_pw = NewPassword {
+            phrase: use_passphrase.then_some(passphrase),
+            allow_weak
+        };
+        let report_path = report_path
+            .unwrap_or_else(|| io::stderr().is_terminal());
+        let timed_show = timed_show || config::TIMED_SHOW;
 
         // Check the validity of the arguments.
         match cmd {
-            Show | Clip
+            Show | Clip | Qr | Extract
             if rec_paths_raw.is_empty() =>
                 return Err(MissingValue { option: None }.into()),
 
-            Clip
+            Clip | Qr | Extract
             if rec_paths_raw.len() > 1 =>
                 // `record_paths` second element was verified to exist.
                 return Err(UnexpectedArgument(
                     take(rec_paths_raw, 1).into()
                 ).into()),
 
-            Edit | ChangePassword | Export | Import
+            Search | AuditReuse | AuditStrength | AuditBreached
+            | Edit | ChangePassword | Export | Import
             if !rec_paths_raw.is_empty() =>
                 // `record_paths` is not empty so its first element exists.
                 return Err(UnexpectedArgument(
                     take(rec_paths_raw, 0).into()
                 ).into()),
 
+            Show | List | Tree | Search | AuditReuse | AuditStrength
+            | AuditBreached => (),
+
+            _ if format == output::Format::Json => return Err(Custom(
+                "option '--json' is not supported by the chosen command".into()
+            ).into()),
+
             _ => ()
         }
 
@@ -321,30 +613,56 @@ impl FileCmd {
             .collect::<Vec<_>>();
 
         Ok(match cmd {
-            Show => Read(ReadCmd::Show(rec_paths, match_kind)),
-            Clip => Read(ReadCmd::Clip(take(rec_paths, 0), match_kind, clip_time)),
-            List => Read(ReadCmd::List(empty_or_some(rec_paths), match_kind)),
-            Tree => Read(ReadCmd::Tree(empty_or_some(rec_paths), match_kind)),
-
-            Edit => Change(ChangeCmd::Modify(Config { match_kind, clip_time })),
-            ChangePassword => Change(ChangeCmd::ChangePassword),
+            Show => Read(ReadCmd::Show {
+                paths: rec_paths,
+                mk: match_kind,
+                attachments: show_attachments,
+                time: timed_show.then_some(clip.time),
+                raw: raw_show,
+                format,
+                report: report_path
+            }),
+            Clip => Read(ReadCmd::Clip(
+                take(rec_paths, 0), match_kind, clip_fields, clip, report_path
+            )),
+            Qr => Read(ReadCmd::Qr(
+                take(rec_paths, 0), match_kind, clip.time, report_path
+            )),
+            List => Read(ReadCmd::List(
+                empty_or_some(rec_paths), match_kind, format, report_path
+            )),
+            Tree => Read(ReadCmd::Tree(
+                empty_or_some(rec_paths), match_kind, format, report_path
+            )),
+            Search => Read(ReadCmd::Search(
+                search_pat, match_kind, search_values, format
+            )),
+            Extract => Read(ReadCmd::Extract(
+                take(rec_paths, 0), match_kind, extract_path
+            )),
+            AuditReuse => Read(ReadCmd::AuditReuse(default_only, format)),
+            AuditStrength => Read(ReadCmd::AuditStrength(format)),
+            AuditBreached =>
+                Read(ReadCmd::AuditBreached(breaches_path, format)),
+
+            Edit => Change(ChangeCmd::Modify(Config {
+                match_kind,
+                clip,
+                timed_show,
+                raw_show,
+                report_path,
+                gen_policy,
+                passphrase
+            })),
+            ChangePassword => Change(ChangeCmd::ChangePassword(new_pw)),
 
             Export => Read(ReadCmd::Export),
-            Import => Create(CreateCmd::Import),
-            CreateEmpty => Create(CreateCmd::CreateEmpty(root_name))
-        })
-    }
-}
+            Import => Create(CreateCmd::Import(new_pw)),
+            CreateEmpty => Create(CreateCmd::CreateEmpty(root_name, new_pw)),
 
-impl Default for FileCmdOpts {
-    fn default() -> Self {
-        Self {
-            /// The path of the target `Record`, root group by default.
-            record_paths_raw: Default::default(),
-            match_kind: Default::default(),
-            clip_time: config::DEFAULT_CLIP_TIME,
-            root_name: Default::default(),
-        }
+            // Handled before any pass file is resolved.
+            Generate | ClearClip | HoldClip => unreachable!()
+        })
     }
 }
 
@@ -359,6 +677,16 @@ impl FileCmdVerb {
     }
 }
 
+/// Returns the directory where processes holding items in the clipboard
+/// register, or `None` if there is no XDG runtime directory.
+pub fn clip_holders_dir() -> Option<PathBuf> {
+    const HOLDERS_DIR: &str = "clip";
+
+    xdg_path::runtime_dir(PROGNAME)
+        .ok()
+        .map(|d| joined(d, HOLDERS_DIR))
+}
+
 /// Returns a [`SafePath`] with `file_path` as the main path, and a file
 /// located in a subdirectory of `data_dir` as the backup path.
 ///
@@ -3,7 +3,8 @@ use crate::{
     backup,
     input_pw,
     serial,
-    find
+    find,
+    gen
 };
 
 use crate::{
@@ -31,6 +32,8 @@ use std::{
 
 use std::fmt::Display;
 
+use std::path::PathBuf;
+
 /// Program errors.
 pub enum Error {
     Environment(env::Error),
@@ -42,6 +45,11 @@ pub enum Error {
     Crypt(crypt::Error),
     OpeningFile(file::Error, file::Mode, SafePath),
     ReadingStdin(user_io::Error),
+    WritingStdout(user_io::Error),
+    /// Displaying data meant for a terminal elsewhere.
+    StdoutNotTerminal,
+    /// Displaying secrets outside a terminal without being allowed to.
+    RefusingNonTerminal,
     FileSerial(serial::Error),
     InputSerial(serial::Error),
 
@@ -49,11 +57,23 @@ pub enum Error {
     /// name of the record, and the group to which it is added
     AddingRecord(record::Error, String, String),
     SerialisingRecord(serial::Error),
+    /// name of the item
+    UnexpectedAttachment(String),
+    /// name of the item
+    ReplacingAttachment(String),
+    /// name of the item
+    EncodingQrCode(String),
+    ReadingAttachment(file::Error, PathBuf),
+    WritingAttachment(file::Error, PathBuf),
+    ReadingBreaches(io::Error, PathBuf),
+    GeneratingPassword(gen::Error),
 
     Clipboard(clip::Error),
     SecuringMemory(proc::Error),
     ExposingMemory(proc::Error),
-    StartingProcess(proc::Error),
+    StartingProcess(io::Error),
+    ControllingProcess(io::Error),
+    StoppingProcesses(clip::holders::Error),
 
     RecoveringBackup(backup::Error, SafePath),
     MakingBackup(file::Error, SafePath),
@@ -78,7 +98,10 @@ pub enum Advice {
     RemovingFile,
     InvalidFile,
     IncorrectPassword,
-    InvalidInput
+    InvalidInput,
+    ExtractingAttachment,
+    ShowingRaw,
+    AllowingWeak
 }
 
 impl Error {
@@ -124,6 +147,12 @@ impl Error {
                 Advice::InvalidFile,
             InputSerial(..) =>
                 Advice::InvalidInput,
+            UnexpectedAttachment(..) =>
+                Advice::ExtractingAttachment,
+            RefusingNonTerminal =>
+                Advice::ShowingRaw,
+            InputPw(input_pw::Error::WeakGenerated) =>
+                Advice::AllowingWeak,
 
             RecoveringBackup(RemovalRefusal, ..) =>
                 Advice::MovingBackup,
@@ -170,6 +199,13 @@ impl Display for Error {
             }
             ReadingStdin(e) =>
                 write!(f, "cannot read stdin: {e}"),
+            WritingStdout(e) =>
+                write!(f, "cannot write to stdout: {e}"),
+            StdoutNotTerminal =>
+                write!(f, "stdout is not a terminal"),
+            RefusingNonTerminal => write!(
+                f, "refusing to display secrets: stdout is not a terminal"
+            ),
             FileSerial(e) =>
                 write!(f, "invalid file contents: {e}"),
             InputSerial(e) =>
@@ -181,6 +217,20 @@ impl Display for Error {
                 write!(f, "cannot create '{name}' in '{dest}': {e}"),
             SerialisingRecord(e) =>
                 write!(f, "{e}"),
+            UnexpectedAttachment(name) =>
+                write!(f, "'{name}' is an attachment"),
+            ReplacingAttachment(name) =>
+                write!(f, "cannot replace attachment '{name}' with text"),
+            EncodingQrCode(name) =>
+                write!(f, "'{name}' is too long for a QR code"),
+            ReadingAttachment(e, p) =>
+                write!(f, "cannot read '{}': {e}", p.display()),
+            WritingAttachment(e, p) =>
+                write!(f, "cannot write '{}': {e}", p.display()),
+            ReadingBreaches(e, p) =>
+                write!(f, "cannot read breach data '{}': {e}", p.display()),
+            GeneratingPassword(e) =>
+                write!(f, "cannot generate password: {e}"),
 
             Clipboard(e) =>
                 write!(f, "{e}"),
@@ -190,6 +240,10 @@ impl Display for Error {
                 write!(f, "cannot disable process memory protections: {e}"),
             StartingProcess(e) =>
                 write!(f, "cannot start clipboard process: {e}"),
+            ControllingProcess(e) =>
+                write!(f, "cannot control clipboard process: {e}"),
+            StoppingProcesses(e) =>
+                write!(f, "cannot stop clipboard processes: {e}"),
 
             RecoveringBackup(e, p) =>
                 write!(f, "cannot recover backup '{}': {e}", p.backup.display()),
@@ -235,6 +289,12 @@ impl From<find::Error> for Error {
     }
 }
 
+impl From<gen::Error> for Error {
+    fn from(e: gen::Error) -> Self {
+        Self::GeneratingPassword(e)
+    }
+}
+
 impl From<clip::Error> for Error {
     fn from(e: clip::Error) -> Self {
         Self::Clipboard(e)
@@ -272,7 +332,13 @@ impl Display for Advice {
                 // TODO: point to ron documentation/examples or something
                 write!(f, "The input format might be invalid."),
             IncorrectPassword =>
-                write!(f, "The entered password may be incorrect.")
+                write!(f, "The entered password may be incorrect."),
+            ExtractingAttachment =>
+                write!(f, "Try extracting it to a file instead."),
+            ShowingRaw =>
+                write!(f, "Try '{PROGNAME} -z' to display them anyway."),
+            AllowingWeak =>
+                write!(f, "Try more words, or '{PROGNAME} -o' to accept it.")
         }
     }
 }
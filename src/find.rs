@@ -1,9 +1,14 @@
-use MatchKind::{Fuzzy, Exact};
+use MatchKind::{Fuzzy, Flat, Exact, Prefix, Substring, SmartCase};
 
-use crate::config::DEFAULT_ITEM;
+use crate::config::{DEFAULT_ITEM, MAX_MATCH_CANDIDATES};
 
 use crate::util::record;
 
+use crate::util::glob::{
+    GlobToken,
+    glob_matches, glob_tokens, is_special, unescape
+};
+
 use crate::util::record::{
     Record, Group, Item,
     Node
@@ -13,10 +18,22 @@ use std::fmt;
 
 use std::fmt::Display;
 
-use std::rc::Rc;
+use std::{
+    rc::Rc,
+    borrow::Cow
+};
 
 /// XXX: an empty path is root
-#[derive(Debug)]
+///
+/// Path elements are separated by [`RecordPath::DELIM`]. A delimiter within a
+/// record name is escaped by prefixing it with [`RecordPath::ESCAPE`], which
+/// escapes itself and wildcards in the same way.
+///
+/// An element containing the wildcards [`RecordPath::ANY_CHARS`] or
+/// [`RecordPath::ANY_CHAR`] is a glob pattern matching several record names,
+/// and an element consisting of [`RecordPath::ANY_DEPTH`] matches any number
+/// of nested records. Such a path may therefore resolve to several records.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RecordPath(String);
 
 /// The method by which a record is searched for.
@@ -26,8 +43,20 @@ pub enum MatchKind {
     /// algorithm.
     #[default]
     Fuzzy,
+    /// Matches the whole path to the full paths of all records using a fuzzy
+    /// matching algorithm.
+    Flat,
     /// Matches each element of the path to record names.
-    Exact
+    Exact,
+    /// Matches each element of the path to the beginning of record names,
+    /// using smart case.
+    Prefix,
+    /// Matches each element of the path to any part of record names, using
+    /// smart case.
+    Substring,
+    /// Matches each element of the path to record names, ignoring case unless
+    /// the element contains uppercase letters.
+    SmartCase
 }
 
 #[derive(Debug)]
@@ -42,40 +71,165 @@ pub enum Error {
     /// found (in which case the pattern was the name).
     NotAGroup { name: String, pat: Option<String> },
     /// Expected an item, but got `rec` matching `pat` instead.
-    NotAnItem { name: String, pat: Option<String> }
+    NotAnItem { name: String, pat: Option<String> },
+    /// Could not find any record matching `pat` while searching.
+    NoMatches { pat: String },
+    /// Expected a single record, but found several matching `pat`.
+    ///
+    /// `candidates` contains the paths of at most [`MAX_MATCH_CANDIDATES`] of
+    /// them, from best to worst match. One of them may be chosen to resume the
+    /// search with `choices`. See [`RecordPath::find_choosing`].
+    MultipleMatches {
+        pat: String,
+        candidates: Vec<RecordPath>,
+        choices: Choices
+    }
+}
+
+/// The records matching a target equally well, and the rest of the target to
+/// find within the one chosen.
+pub struct Choices {
+    recs: Vec<Node<Record>>,
+    rest: RecordPath
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-pub type SplitResult<T> = std::result::Result<(T, T), T>;
+/// A path split into its parent path and the name of its trailing element.
+pub type SplitResult = std::result::Result<(RecordPath, String), RecordPath>;
 
 impl RecordPath {
     pub const DELIM: char = '.';
+    pub const ESCAPE: char = '\\';
+    pub const ANY_CHARS: char = '*';
+    pub const ANY_CHAR: char = '?';
+    pub const ANY_DEPTH: &'static str = "**";
+
+    /// Returns a path whose elements are exactly `names`, escaping them as
+    /// necessary.
+    pub fn from_names<'n, I>(names: I) -> Self
+        where
+            I: IntoIterator<Item = &'n str>
+    {
+        let mut result = Self(String::new());
+
+        for n in names {
+            result.push(n);
+        }
+
+        result
+    }
 
-    pub fn iter(&self) -> impl Iterator<Item = &str> {
-        self.0
-            .split(Self::DELIM)
-            .filter(|e| !e.is_empty())      // Ignore extraneous delimiters.
+    /// Returns the names matched by each element of the path, with escape
+    /// sequences resolved.
+    pub fn iter(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        self.raw_iter().map(|(_, e)| unescape(e))
+    }
+
+    /// Appends `name` to the path as a new trailing element, escaping it as
+    /// necessary.
+    pub fn push(&mut self, name: &str) {
+        if !self.0.is_empty() {
+            self.0.push(Self::DELIM);
+        }
+
+        for c in name.chars() {
+            if is_special(c) {
+                self.0.push(Self::ESCAPE);
+            }
+
+            self.0.push(c);
+        }
     }
 
     /// if self is not root, returns its parent path, and the trailing path
-    /// element. returned trailing element won't have any delimiters
+    /// element. returned trailing element is unescaped, and therefore a valid
+    /// record name
     /// if self is root, returns err and does not modify
     /// if self only has 1 element, leading will be empty and trailing will
     /// contain it
-    pub fn split_last(mut self) -> SplitResult<RecordPath> {
-        let trailing = match self.iter().last() {
-            Some(e) => RecordPath::from(e.to_owned()),
+    pub fn split_last(mut self) -> SplitResult {
+        let (start, trailing) = match self.raw_iter().last() {
+            Some((start, e)) => (start, unescape(e).into_owned()),
             None => return Err(self)
         };
 
-        self.strip_last();
+        // Remove the trailing path element, keeping the parent path.
+        self.0.truncate(start);
 
         Ok((self, trailing))
     }
 
-    pub fn into_inner(self) -> String {
-        self.0
+    /// Returns the path of `rec` relative to the root group containing it.
+    pub fn of(rec: &Record) -> Self {
+        let mut names = Vec::new();
+        let mut name = rec.do_with_meta(|meta| meta.name().to_owned());
+        let mut parent = rec.parent();
+
+        // The name of the root group is not part of the path.
+        while let Some(group) = parent {
+            let group = group.borrow();
+
+            names.push(name);
+            name = group.name().to_owned();
+            parent = group.parent();
+        }
+
+        Self::from_names(names.iter().rev().map(String::as_str))
+    }
+
+    /// Verifies if the path contains glob patterns, and may therefore resolve
+    /// to several records.
+    pub fn is_glob(&self) -> bool {
+        self.elements().any(|e| !matches!(e, Element::Name(_)))
+    }
+
+    /// Finds every record matching the path within `rec` or its children.
+    ///
+    /// Elements which aren't glob patterns are matched according to `mk`.
+    /// Fails if no record matches. If the path contains no glob patterns, this
+    /// is equivalent to [`RecordPath::find_in`].
+    pub fn find_all_in(
+        &self,
+        rec: &Node<Record>,
+        mk: MatchKind
+    ) -> Result<Vec<Node<Record>>> {
+        if !self.is_glob() {
+            return Ok(vec![self.find_in(rec, mk)?]);
+        }
+
+        let elems = self.elements().collect::<Vec<_>>();
+        let mut result = Vec::new();
+
+        find_globbed(rec, &elems, mk, &mut result);
+
+        if result.is_empty() {
+            Err(Error::NoMatches { pat: self.to_string() })
+        } else {
+            Ok(result)
+        }
+    }
+
+    /// Like [`RecordPath::find_all_in`], but also returns the full path of each
+    /// record found.
+    pub fn resolve_all_in(
+        &self,
+        rec: &Node<Record>,
+        mk: MatchKind
+    ) -> Result<Vec<(RecordPath, Node<Record>)>> {
+        if !self.is_glob() {
+            let found = self.find_rec_in(rec, mk)?;
+            return Ok(vec![(found.path, found.rec)]);
+        }
+
+        let found = self.find_all_in(rec, mk)?.into_iter()
+            .map(|r| {
+                let path = RecordPath::of(&r.borrow());
+                (path, r)
+            })
+            .collect();
+
+        Ok(found)
     }
 
     pub fn find_in(
@@ -93,7 +247,7 @@ impl RecordPath {
         rec: &Node<Record>,
         mk: MatchKind
     ) -> Result<Node<Group>> {
-        let FoundRecord { rec, matched_pat } = self.find_rec_in(rec, mk)?;
+        let FoundRecord { rec, matched_pat, .. } = self.find_rec_in(rec, mk)?;
         let rec = rec.borrow();
 
         match &*rec {
@@ -111,7 +265,7 @@ impl RecordPath {
         rec: &Node<Record>,
         mk: MatchKind
     ) -> Result<Node<Item>> {
-        let FoundRecord { rec, matched_pat } = self.find_rec_in(rec, mk)?;
+        let FoundRecord { rec, matched_pat, .. } = self.find_rec_in(rec, mk)?;
         let rec = rec.borrow();
 
         match &*rec {
@@ -127,19 +281,54 @@ impl RecordPath {
     /// XXX:
     /// if item is found, return that
     /// if group is found, return `DEFAULT_ITEM` directly inside it if it exists
-    pub fn find_item_or_default_in(
+    /// - also returns the full path of the item
+    pub fn resolve_item_or_default_in(
         &self,
         rec: &Node<Record>,
         mk: MatchKind
-    ) -> Result<Node<Item>> {
-        let found = self.find_in(rec, mk)?;
-        let found_ref = &*found.borrow();
+    ) -> Result<(RecordPath, Node<Item>)> {
+        let FoundRecord { rec, path, .. } = self.find_rec_in(rec, mk)?;
 
-        match found_ref {
-            Record::Group(_) => RecordPath::from(DEFAULT_ITEM)
-                .find_item_in(&found, Exact),
+        resolved_item_or_default(&rec, path)
+    }
 
-            Record::Item(i) => Ok(Rc::clone(i))
+    /// Finds the path within `rec` with `find`, which is one of the methods
+    /// finding a record like [`RecordPath::find_in`].
+    ///
+    /// Whenever several records match the path equally well, `choose` is
+    /// given the pattern they match and their paths, and returns the index of
+    /// the one to resume the search with. The search fails with
+    /// [`Error::MultipleMatches`] if it returns `None`.
+    pub fn find_choosing<T, F, C>(
+        &self,
+        rec: &Node<Record>,
+        find: F,
+        choose: C
+    ) -> Result<T>
+        where
+            F: Fn(&RecordPath, &Node<Record>) -> Result<T>,
+            C: Fn(&str, &[RecordPath]) -> Option<usize>
+    {
+        let mut path = Cow::Borrowed(self);
+        let mut rec = Rc::clone(rec);
+
+        loop {
+            let (pat, candidates, choices) = match find(&path, &rec) {
+                Err(Error::MultipleMatches { pat, candidates, choices }) =>
+                    (pat, candidates, choices),
+                result => return result
+            };
+
+            match choose(&pat, &candidates) {
+                Some(i) if i < choices.recs.len() => {
+                    rec = Rc::clone(&choices.recs[i]);
+                    path = Cow::Owned(choices.rest);
+                }
+
+                _ => return Err(Error::MultipleMatches {
+                    pat, candidates, choices
+                })
+            }
         }
     }
 }
@@ -151,21 +340,179 @@ impl<S: Into<String>> From<S> for RecordPath {
 }
 
 impl Display for RecordPath {
+    /// Displays the path in its escaped form, such that it can be passed back
+    /// as a target.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.0.fmt(f)
     }
 }
 
+/// Returns every item within `rec` along with its full path, in depth-first
+/// order. If `rec` is an item, only it is returned.
+pub fn items_in(rec: &Node<Record>) -> Vec<(RecordPath, Node<Item>)> {
+    fn add_members(
+        group: &Group,
+        path: &RecordPath,
+        dest: &mut Vec<(RecordPath, Node<Item>)>
+    ) {
+        for (name, rec) in group.members() {
+            let mut rec_path = path.clone();
+
+            rec_path.push(name);
+
+            match &*rec.borrow() {
+                Record::Group(g) => add_members(&g.borrow(), &rec_path, dest),
+                Record::Item(i) => dest.push((rec_path, Rc::clone(i)))
+            }
+        }
+    }
+
+    let path = RecordPath::of(&rec.borrow());
+    let mut result = Vec::new();
+
+    match &*rec.borrow() {
+        Record::Group(g) => add_members(&g.borrow(), &path, &mut result),
+        Record::Item(i) => result.push((path, Rc::clone(i)))
+    }
+
+    result
+}
+
+/// Searches for records within `rec` whose names match `pat`, or whose values
+/// match it if `values` is true.
+///
+/// Returns the paths of the matching records relative to `rec`, in depth-first
+/// order. Values are never returned, and attachments are never matched.
+pub fn search_in(
+    rec: &Node<Record>,
+    pat: &str,
+    mk: MatchKind,
+    values: bool
+) -> Result<Vec<RecordPath>> {
+    let mut result = Vec::new();
+
+    if let Record::Group(g) = &*rec.borrow() {
+        let root = RecordPath::from(String::new());
+
+        search_group(&g.borrow(), &root, pat, mk, values, &mut result);
+    }
+
+    if result.is_empty() {
+        Err(Error::NoMatches { pat: pat.to_owned() })
+    } else {
+        Ok(result)
+    }
+}
+
+/// XXX:
+/// if `rec` is an item, return that
+/// if it is a group, return `DEFAULT_ITEM` directly inside it if it exists
+pub fn item_or_default(rec: &Node<Record>) -> Result<Node<Item>> {
+    match &*rec.borrow() {
+        Record::Group(_) => RecordPath::from_names([DEFAULT_ITEM])
+            .find_item_in(rec, Exact),
+
+        Record::Item(i) => Ok(Rc::clone(i))
+    }
+}
+
+/// Like [`item_or_default`], but also returns the full path of the item given
+/// `path`, the full path of `rec`.
+pub fn resolved_item_or_default(
+    rec: &Node<Record>,
+    mut path: RecordPath
+) -> Result<(RecordPath, Node<Item>)> {
+    let item = item_or_default(rec)?;
+
+    if let Record::Group(_) = &*rec.borrow() {
+        path.push(DEFAULT_ITEM);
+    }
+
+    Ok((path, item))
+}
+
 impl MatchKind {
+    /// Verifies if `s` matches `pat` according to `self`.
+    pub fn matches(self, pat: &str, s: &str) -> bool {
+        match self {
+            Fuzzy | Flat => sublime_fuzzy::best_match(pat, s).is_some(),
+            Exact => pat == s,
+
+            // Case is ignored unless `pat` contains uppercase letters. `s` is
+            // lowercased as it is compared, as it may be a secret value.
+            Prefix | Substring | SmartCase =>
+                if pat.chars().any(char::is_uppercase) {
+                    self.matches_chars(pat, s.chars())
+                } else {
+                    let lower = s.chars().flat_map(char::to_lowercase);
+                    self.matches_chars(pat, lower)
+                }
+        }
+    }
+
+    /// Verifies if the characters `s` match `pat` according to `self`, which
+    /// must be [`Prefix`], [`Substring`] or [`SmartCase`].
+    fn matches_chars<I>(self, pat: &str, mut s: I) -> bool
+        where
+            I: Iterator<Item = char> + Clone
+    {
+        let starts_with = |mut s: I| pat.chars().all(|c| s.next() == Some(c));
+
+        match self {
+            Prefix => starts_with(s),
+
+            Substring => loop {
+                if starts_with(s.clone()) {
+                    break true;
+                }
+
+                if s.next().is_none() {
+                    break false;
+                }
+            },
+
+            _ => s.eq(pat.chars())
+        }
+    }
+
+    /// Parses `s` as the name of a `MatchKind` or as its abbreviation.
+    ///
+    /// An ambiguous abbreviation is resolved in favour of the first matching
+    /// name in the order "exact", "fuzzy", "flat", "prefix", "substring",
+    /// "smart-case".
     pub fn from_str(s: &str) -> Option<Self> {
-        // "exact" and "fuzzy" are completely distinct strings, so the following
-        // won't have unexpected results.
-        if "exact".starts_with(s) {
-            Some(Exact)
-        } else if "fuzzy".starts_with(s) {
-            Some(Fuzzy)
-        } else {
-            None
+        const NAMES: [(&str, MatchKind); 6] = [
+            ("exact", Exact),
+            ("fuzzy", Fuzzy),
+            ("flat", Flat),
+            ("prefix", Prefix),
+            ("substring", Substring),
+            ("smart-case", SmartCase)
+        ];
+
+        if s.is_empty() {
+            return None;
+        }
+
+        NAMES.into_iter()
+            .find(|(name, _)| name.starts_with(s))
+            .map(|(_, mk)| mk)
+    }
+
+    /// Finds the member of `group` whose name matches `pat`.
+    ///
+    /// [`Flat`] matches each name separately, like [`Fuzzy`].
+    fn find_member(
+        self,
+        group: &Group,
+        pat: &str
+    ) -> record::Result<Node<Record>> {
+        match self {
+            Fuzzy | Flat => group.get_fuzzy(pat),
+            Exact => group.get(pat),
+
+            Prefix | Substring | SmartCase =>
+                group.get_matching(pat, |name| self.matches(pat, name))
         }
     }
 }
@@ -173,8 +520,12 @@ impl MatchKind {
 impl Display for MatchKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Fuzzy => f.write_str("fuzzy"),
-            Exact => f.write_str("exact")
+            Fuzzy     => f.write_str("fuzzy"),
+            Flat      => f.write_str("flat"),
+            Exact     => f.write_str("exact"),
+            Prefix    => f.write_str("prefix"),
+            Substring => f.write_str("substring"),
+            SmartCase => f.write_str("smart-case")
         }
     }
 }
@@ -198,16 +549,51 @@ impl Display for Error {
                 Some(pat) => write!(f, "'{pat}': '{name}' is not an item"),
                 None      => write!(f, "'{name}' is not an item")
             }
+
+            NoMatches { pat } =>
+                write!(f, "'{pat}': no matches found"),
+            MultipleMatches { pat, candidates, .. } => {
+                write!(f, "'{pat}': multiple matches found")?;
+
+                for (i, c) in candidates.iter().enumerate() {
+                    let sep = if i == 0 { ':' } else { ',' };
+
+                    write!(f, "{sep} '{c}'")?;
+                }
+
+                Ok(())
+            }
         }
     }
 }
 
+/// A parsed path element.
+enum Element<'p> {
+    /// A record name, with escape sequences resolved.
+    Name(Cow<'p, str>),
+    /// A pattern matching record names, in its escaped form.
+    Glob(&'p str),
+    /// Matches any number of nested records, including none.
+    AnyDepth
+}
+
 struct FoundRecord {
     rec: Node<Record>,
+    /// The full path of `rec`, with each matched name.
+    path: RecordPath,
     /// None if `rec` is root.
     matched_pat: Option<String>
 }
 
+impl FoundRecord {
+    /// Returns a `FoundRecord` whose path is obtained from `rec` itself.
+    fn new(rec: Node<Record>, matched_pat: Option<String>) -> Self {
+        let path = RecordPath::of(&rec.borrow());
+
+        Self { rec, path, matched_pat }
+    }
+}
+
 impl RecordPath {
     /// Finds a record matching the target within `rec` or its children.
     fn find_rec_in(
@@ -215,67 +601,374 @@ impl RecordPath {
         rec: &Node<Record>,
         mk: MatchKind
     ) -> Result<FoundRecord> {
+        // Designates `rec` itself whatever the match kind, which is useful to
+        // resume a search. See `RecordPath::find_choosing`.
+        if self.raw_iter().next().is_none() {
+            return Ok(FoundRecord::new(Rc::clone(rec), None));
+        }
+
+        if self.is_glob() {
+            let found = self.find_all_in(rec, mk)?;
+            let rec = only_of(&self.to_string(), found, Self::root())?;
+
+            return Ok(FoundRecord::new(rec, Some(self.to_string())));
+        }
+
+        if mk == Flat {
+            return self.find_flat_in(rec);
+        }
+
+        let mut path = RecordPath::of(&rec.borrow());
         let mut rec = Rc::clone(rec);
-        let mut matched_pat = Option::<&str>::None;
+        let mut matched_pat = Option::<Cow<str>>::None;
 
-        for pat in self.iter().peekable() {
+        for (n, pat) in self.iter().enumerate() {
             let found = match &*rec.borrow() {
-                Record::Group(g) => match mk {
-                    Fuzzy => g.borrow().get_fuzzy(pat),
-                    Exact => g.borrow().get(pat)
-                }.map_err(|e| Error::NotFound {
-                    e: Box::new(e),
-                    pat: pat.to_owned(),
-                    in_group: g.borrow().name().to_owned()
-                })?,
+                Record::Group(g) => {
+                    let found = mk.find_member(&g.borrow(), &pat);
+
+                    found.or_else(|e| match e {
+                        record::Error::MultipleMatches(names) => {
+                            let g = g.borrow();
+
+                            // The names were just matched so they must exist.
+                            let candidates = names.iter()
+                                .map(|n| g.get(n).unwrap())
+                                .collect();
+
+                            only_of(&pat, candidates, self.skip(n + 1))
+                        }
+
+                        e => Err(Error::NotFound {
+                            e: Box::new(e),
+                            pat: pat.clone().into_owned(),
+                            in_group: g.borrow().name().to_owned()
+                        })
+                    })?
+                }
 
                 Record::Item(i) => return Err(Error::NotAGroup {
                     name: i.borrow().name().to_owned(),
                     pat: match mk {
-                        Fuzzy => Some(pat.to_owned()),
-                        Exact => None
+                        Exact => None,
+                        _ => Some(pat.into_owned())
                     }
                 })
             };
 
+            found.borrow().do_with_meta(|meta| path.push(meta.name()));
+
             rec = found;
             matched_pat = Some(pat);
         }
 
         let matched_pat = matched_pat
             .filter(|_| mk != Exact)    // The pattern equals the record name.
-            .map(ToOwned::to_owned);
+            .map(Cow::into_owned);
+
+        Ok(FoundRecord { rec, path, matched_pat })
+    }
+
+    /// Finds the record within `rec` whose full path best matches the whole
+    /// target using a fuzzy matching algorithm.
+    ///
+    /// If several records match equally well and one of them contains all the
+    /// others, it is preferred.
+    fn find_flat_in(&self, rec: &Node<Record>) -> Result<FoundRecord> {
+        use sublime_fuzzy::best_match;
+
+        let names = self.iter().collect::<Vec<_>>();
+
+        // The root group is not matched by name, as with other `MatchKind`s.
+        if names.is_empty() {
+            return Ok(FoundRecord::new(Rc::clone(rec), None));
+        }
 
-        Ok(FoundRecord { rec, matched_pat })
+        let pat = names.join(&Self::DELIM.to_string());
+
+        let mut matches = full_paths_in(rec).into_iter()
+            .filter_map(|(path, r)| {
+                let score = best_match(&pat, &path)?.score();
+                Some((score, path, r))
+            })
+            .collect::<Vec<_>>();
+
+        // Order by descending score, then by ascending path length such that
+        // containing groups come first.
+        matches.sort_by(|(s1, p1, _), (s2, p2, _)| {
+            s2.cmp(s1).then(p1.len().cmp(&p2.len()))
+        });
+
+        let Some((best_score, _, best)) = matches.first() else {
+            return Err(Error::NoMatches { pat });
+        };
+
+        let is_unambiguous = matches.iter()
+            .take_while(|(s, ..)| s == best_score)
+            .all(|(_, _, r)| is_within(r, best));
+
+        if is_unambiguous {
+            return Ok(FoundRecord::new(Rc::clone(best), Some(pat)));
+        }
+
+        // Records within better matching groups are redundant as candidates.
+        let mut candidates = Vec::<Node<Record>>::new();
+
+        for (.., r) in &matches {
+            if candidates.len() == MAX_MATCH_CANDIDATES {
+                break;
+            }
+
+            if !candidates.iter().any(|c| is_within(r, c)) {
+                candidates.push(Rc::clone(r));
+            }
+        }
+
+        let rec = only_of(&pat, candidates, Self::root())?;
+
+        Ok(FoundRecord::new(rec, Some(pat)))
+    }
+}
+
+/// Returns the only record of `candidates` matching `pat`.
+///
+/// If there are several, this fails with [`Error::MultipleMatches`] listing
+/// them, along with `rest`, the part of the target left to find within the one
+/// chosen.
+fn only_of(
+    pat: &str,
+    mut candidates: Vec<Node<Record>>,
+    rest: RecordPath
+) -> Result<Node<Record>> {
+    if candidates.len() == 1 {
+        return Ok(candidates.pop().unwrap());
+    }
+
+    candidates.truncate(MAX_MATCH_CANDIDATES);
+
+    let paths = candidates.iter()
+        .map(|r| RecordPath::of(&r.borrow()))
+        .collect();
+
+    Err(Error::MultipleMatches {
+        pat: pat.to_owned(),
+        candidates: paths,
+        choices: Choices { recs: candidates, rest }
+    })
+}
+
+impl fmt::Debug for Choices {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Choices")
+            .field("rest", &self.rest)
+            .finish_non_exhaustive()
     }
 }
 
 impl RecordPath {
-    ///  if self is not root, removes its last path element
-    ///  (effectively turning it into its parent path)
-    ///  if it is root, removes any extraneous delimiters if they exist
-    fn strip_last(&mut self) {
-        // TODO: make this code cleaner if possible
-
-        // Remove the extraneous delimiters if they exist to access the
-        // trailing path element.
-        if let Some(Self::DELIM) = self.last_char() {
-            while let Some(Self::DELIM) = self.last_char() {
-                self.0.pop();
+    /// Returns the parsed elements of the path.
+    fn elements(&self) -> impl Iterator<Item = Element<'_>> {
+        self.raw_iter().map(|(_, e)| {
+            if e == Self::ANY_DEPTH {
+                Element::AnyDepth
+            } else if glob_tokens(e).any(|t| !matches!(t, GlobToken::Char(_))) {
+                Element::Glob(e)
+            } else {
+                Element::Name(unescape(e))
+            }
+        })
+    }
+
+    /// Returns the empty path, which designates the root group.
+    fn root() -> Self {
+        Self(String::new())
+    }
+
+    /// Returns the path without its first `n` elements.
+    fn skip(&self, n: usize) -> Self {
+        match self.raw_iter().nth(n) {
+            Some((start, _)) => Self(self.0[start..].to_owned()),
+            None => Self::root()
+        }
+    }
+
+    /// Returns the escaped elements of the path, with the byte index at which
+    /// each of them starts.
+    ///
+    /// Escaped delimiters are not considered as separating elements.
+    fn raw_iter(&self) -> impl Iterator<Item = (usize, &str)> {
+        let path = self.0.as_str();
+        let mut chars = path.char_indices();
+        let mut start = 0;
+        let mut escaping = false;
+        let mut done = false;
+
+        std::iter::from_fn(move || {
+            if done { return None; }
+
+            for (idx, c) in &mut chars {
+                if escaping {
+                    escaping = false;
+                } else if c == Self::ESCAPE {
+                    escaping = true;
+                } else if c == Self::DELIM {
+                    let elem = (start, &path[start..idx]);
+
+                    start = idx + c.len_utf8();
+                    return Some(elem);
+                }
+            }
+
+            done = true;
+            Some((start, &path[start..]))
+        }).filter(|(_, e)| !e.is_empty())   // Ignore extraneous delimiters.
+    }
+}
+
+/// Appends the paths of the matching members of `group` to `dest`, and
+/// recursively searches its subgroups.
+///
+/// `path` is the path of `group`.
+fn search_group(
+    group: &Group,
+    path: &RecordPath,
+    pat: &str,
+    mk: MatchKind,
+    values: bool,
+    dest: &mut Vec<RecordPath>
+) {
+    for (name, rec) in group.members() {
+        let mut rec_path = path.clone();
+
+        rec_path.push(name);
+
+        match &*rec.borrow() {
+            Record::Group(g) => {
+                if mk.matches(pat, name) {
+                    dest.push(rec_path.clone());
+                }
+
+                search_group(&g.borrow(), &rec_path, pat, mk, values, dest);
+            }
+
+            Record::Item(i) => {
+                let value_matches = || {
+                    let i = i.borrow();
+                    let text = i.value().as_text();
+
+                    values && text.is_some_and(|t| mk.matches(pat, t))
+                };
+
+                if mk.matches(pat, name) || value_matches() {
+                    dest.push(rec_path);
+                }
+            }
+        }
+    }
+}
+
+/// Appends the records within `rec` matching `elems` to `dest`, excluding
+/// those already present.
+///
+/// Records that cannot match, like items where a group is expected, are
+/// ignored.
+fn find_globbed(
+    rec: &Node<Record>,
+    elems: &[Element],
+    mk: MatchKind,
+    dest: &mut Vec<Node<Record>>
+) {
+    let Some((elem, rest)) = elems.split_first() else {
+        // A record may be reached several times with `Element::AnyDepth`.
+        if !dest.iter().any(|r| Rc::ptr_eq(r, rec)) {
+            dest.push(Rc::clone(rec));
+        }
+
+        return;
+    };
+
+    if let Element::AnyDepth = elem {
+        // Match no records at all.
+        find_globbed(rec, rest, mk, dest);
+    }
+
+    let Record::Group(g) = &*rec.borrow() else {
+        return;
+    };
+
+    let g = g.borrow();
+
+    match elem {
+        Element::Name(pat) => {
+            // Full paths cannot be matched within glob patterns, so each
+            // element is matched separately.
+            if let Ok(found) = mk.find_member(&g, pat) {
+                find_globbed(&found, rest, mk, dest);
+            }
+        }
+
+        Element::Glob(pat) => for (name, member) in g.members() {
+            if glob_matches(pat, name) {
+                find_globbed(member, rest, mk, dest);
             }
         }
 
-        // TODO: replace with `while self.last_char().is_some_and(|c| c != Self::DELIM)`
-        //  or let chains when available
+        // Match one or more records.
+        Element::AnyDepth => for (_, member) in g.members() {
+            find_globbed(member, elems, mk, dest);
+        }
+    }
+}
+
+/// Returns every record within `rec` along with its full path relative to
+/// `rec`, with names separated by [`RecordPath::DELIM`] and unescaped.
+fn full_paths_in(rec: &Node<Record>) -> Vec<(String, Node<Record>)> {
+    type Paths = Vec<(String, Node<Record>)>;
+
+    fn add_members(group: &Group, path: &str, dest: &mut Paths) {
+        for (name, rec) in group.members() {
+            let rec_path = if path.is_empty() {
+                name.to_owned()
+            } else {
+                format!("{path}{}{name}", RecordPath::DELIM)
+            };
+
+            if let Record::Group(g) = &*rec.borrow() {
+                add_members(&g.borrow(), &rec_path, dest);
+            }
 
-        // Remove the trailing path element.
-        while let Some(c) = self.last_char() {
-            if c == Self::DELIM { break; }
-            self.0.pop();
+            dest.push((rec_path, Rc::clone(rec)));
         }
     }
 
-    fn last_char(&self) -> Option<char> {
-        self.0.chars().last()
+    let mut result = Vec::new();
+
+    if let Record::Group(g) = &*rec.borrow() {
+        add_members(&g.borrow(), "", &mut result);
     }
+
+    result
+}
+
+/// Verifies if `rec` is `group` or is contained within it.
+fn is_within(rec: &Node<Record>, group: &Node<Record>) -> bool {
+    if Rc::ptr_eq(rec, group) {
+        return true;
+    }
+
+    let Record::Group(group) = &*group.borrow() else {
+        return false;
+    };
+
+    let mut parent = rec.borrow().parent();
+
+    while let Some(p) = parent {
+        if Rc::ptr_eq(&p, group) {
+            return true;
+        }
+
+        parent = p.borrow().parent();
+    }
+
+    false
 }
@@ -0,0 +1,245 @@
+pub mod passphrase;
+pub mod rules;
+
+pub use passphrase::Passphrase;
+pub use rules::Rules;
+
+use crate::config;
+
+use crate::util::secret::Secret;
+
+use std::fmt;
+
+use std::fmt::Display;
+
+const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// The names of the character classes, in the order of the fields of
+/// [`Classes`].
+const CLASS_NAMES: [&str; 4] = [
+    "lowercase letters", "uppercase letters", "digits", "symbols"
+];
+
+/// Characters easily confused with one another in some fonts.
+const AMBIGUOUS: &str = "0Oo1Il|`'\"";
+
+/// How random passwords are generated.
+#[derive(Clone, Copy)]
+pub struct Policy {
+    pub length: usize,
+    pub classes: Classes,
+    /// Whether characters from [`AMBIGUOUS`] may be used.
+    pub ambiguous: bool
+}
+
+/// The character classes a generated password is made of. Each enabled class
+/// appears at least once in the password.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Classes {
+    pub lower: bool,
+    pub upper: bool,
+    pub digits: bool,
+    pub symbols: bool
+}
+
+pub enum Error {
+    /// The length is lower than the number of classes, so they cannot all
+    /// appear in the password.
+    TooShort(usize, usize),
+    /// Every class is forbidden by a group's policy.
+    NoClasses,
+    /// No password satisfying a group's policy could be generated.
+    Unsatisfiable,
+    /// The key and value of an invalid policy rule.
+    InvalidRule(String, String)
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Policy {
+    /// Returns a password made of uniformly random characters of the enabled
+    /// classes, drawn from the operating system's secure random source.
+    ///
+    /// Passwords lacking a class are discarded and drawn again, so that every
+    /// valid password remains equally likely.
+    pub fn generate(&self) -> Result<Secret<String>> {
+        use rand::Rng;
+        use rand::rngs::OsRng;
+
+        let sets = self.char_sets();
+
+        if self.length < sets.len() {
+            return Err(Error::TooShort(self.length, sets.len()));
+        }
+
+        let alphabet = sets.concat();
+
+        loop {
+            let mut pw = Secret::new(String::with_capacity(self.length));
+
+            for _ in 0..self.length {
+                // `OsRng` implements `CryptoRng` so it is cryptographically
+                // secure, and `gen_range` is unbiased.
+                pw.push(alphabet[OsRng.gen_range(0..alphabet.len())]);
+            }
+
+            let has_all = sets.iter()
+                .all(|set| pw.chars().any(|c| set.contains(&c)));
+
+            if has_all {
+                return Ok(pw);
+            }
+        }
+    }
+
+    /// Returns the characters of each enabled class, without the ambiguous
+    /// ones unless allowed.
+    fn char_sets(&self) -> Vec<Vec<char>> {
+        let Classes { lower, upper, digits, symbols } = self.classes;
+
+        [(lower, LOWER), (upper, UPPER), (digits, DIGITS), (symbols, SYMBOLS)]
+            .into_iter()
+            .filter(|(enabled, _)| *enabled)
+            .map(|(_, set)| set.chars()
+                .filter(|c| self.ambiguous || !AMBIGUOUS.contains(*c))
+                .collect())
+            .collect()
+    }
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            length: config::DEFAULT_GEN_LENGTH,
+            classes: Classes::default(),
+            ambiguous: true
+        }
+    }
+}
+
+impl Classes {
+    pub const NONE: Self = Self::from_array([false; 4]);
+
+    /// Returns the classes of the characters in `s`, ignoring characters of no
+    /// class.
+    pub fn of(s: &str) -> Self {
+        let contains = |set: &str| s.chars().any(|c| set.contains(c));
+
+        Self::from_array([LOWER, UPPER, DIGITS, SYMBOLS].map(contains))
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        self.zip_with(other, |a, b| a || b)
+    }
+
+    pub fn intersection(self, other: Self) -> Self {
+        self.zip_with(other, |a, b| a && b)
+    }
+
+    pub fn without(self, other: Self) -> Self {
+        self.zip_with(other, |a, b| a && !b)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self == Self::NONE
+    }
+
+    /// Returns the full names of the classes.
+    pub fn names(self) -> Vec<&'static str> {
+        self.to_array().into_iter()
+            .zip(CLASS_NAMES)
+            .filter_map(|(enabled, name)| enabled.then_some(name))
+            .collect()
+    }
+
+    /// Parses classes from their initials: 'l' for lowercase letters, 'u' for
+    /// uppercase letters, 'd' for digits and 's' for symbols.
+    ///
+    /// Returns `None` if `s` is empty or contains any other character.
+    pub fn from_str(s: &str) -> Option<Self> {
+        if s.is_empty() {
+            return None;
+        }
+
+        let mut result = Self::NONE;
+
+        for c in s.chars() {
+            match c {
+                'l' => result.lower = true,
+                'u' => result.upper = true,
+                'd' => result.digits = true,
+                's' => result.symbols = true,
+                 _  => return None
+            }
+        }
+
+        Some(result)
+    }
+
+    const fn from_array([lower, upper, digits, symbols]: [bool; 4]) -> Self {
+        Self { lower, upper, digits, symbols }
+    }
+
+    fn to_array(self) -> [bool; 4] {
+        [self.lower, self.upper, self.digits, self.symbols]
+    }
+
+    fn zip_with<F>(self, other: Self, f: F) -> Self
+        where
+            F: Fn(bool, bool) -> bool
+    {
+        let (a, b) = (self.to_array(), other.to_array());
+
+        Self::from_array([0, 1, 2, 3].map(|i| f(a[i], b[i])))
+    }
+}
+
+impl Default for Classes {
+    fn default() -> Self {
+        Self {
+            lower: true,
+            upper: true,
+            digits: true,
+            symbols: true
+        }
+    }
+}
+
+impl Display for Classes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Self { lower, upper, digits, symbols } = *self;
+
+        let flags = [
+            (lower, 'l'), (upper, 'u'), (digits, 'd'), (symbols, 's')
+        ];
+
+        for (enabled, c) in flags {
+            if enabled {
+                write!(f, "{c}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Error::*;
+
+        match self {
+            TooShort(len, classes) => write!(
+                f, "cannot fit {classes} character classes in {len} characters"
+            ),
+            NoClasses =>
+                write!(f, "every character class is forbidden by the policy"),
+            Unsatisfiable =>
+                write!(f, "cannot satisfy the policy"),
+            InvalidRule(k, v) =>
+                write!(f, "invalid policy rule '{k}={v}'")
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::config;
+
+use crate::util::secret::Secret;
+
+use std::num::NonZeroUsize;
+
+/// The words passphrases are made of, one per line.
+///
+/// Kept apart from the dictionaries of the strength estimator, so that either
+/// may change without affecting the other. Each word adds the base 2 logarithm
+/// of the number of words to the entropy (see [`Passphrase::entropy`]).
+///
+/// XXX: still the BIP-39 English list (2048 words, 11 bits each) until the EFF
+/// large list (7776 words, about 12.9 bits each) is vendored here.
+const WORDS: &str = include_str!("words.txt");
+
+/// How random passphrases are generated.
+#[derive(Clone, Copy)]
+pub struct Passphrase {
+    pub words: NonZeroUsize,
+    /// Inserted between words.
+    pub separator: char,
+    /// Whether the first letter of each word is capitalised.
+    pub capitalise: bool,
+    /// Whether a random digit is appended to a random word.
+    pub digit: bool
+}
+
+impl Passphrase {
+    /// Returns a passphrase made of uniformly random words from [`WORDS`],
+    /// drawn from the operating system's secure random source.
+    pub fn generate(&self) -> Secret<String> {
+        use rand::Rng;
+        use rand::rngs::OsRng;
+
+        let list = word_list();
+        let words = self.words.get();
+
+        // `OsRng` implements `CryptoRng` so it is cryptographically secure,
+        // and `gen_range` is unbiased.
+        let digit_pos = OsRng.gen_range(0..words);
+        let digit = OsRng.gen_range(0..10u32);
+
+        // Allocate the maximum length in advance, so that the passphrase is
+        // never reallocated (which would leave unerased copies behind). The
+        // words are ASCII, so capitalising them doesn't change their length.
+        let max_word = list.iter().map(|w| w.len()).max().unwrap_or(0);
+        let capacity = words * (max_word + self.separator.len_utf8()) + 1;
+        let mut result = Secret::new(String::with_capacity(capacity));
+
+        for i in 0..words {
+            let word = list[OsRng.gen_range(0..list.len())];
+            let mut chars = word.chars();
+
+            if i > 0 {
+                result.push(self.separator);
+            }
+
+            if self.capitalise {
+                // Words are never empty.
+                result.extend(chars.next().unwrap().to_uppercase());
+            }
+
+            result.extend(chars);
+
+            if self.digit && i == digit_pos {
+                // `digit` is lower than 10, so it is a valid digit.
+                result.push(char::from_digit(digit, 10).unwrap());
+            }
+        }
+
+        result
+    }
+
+    /// Returns the entropy in bits of the generated passphrases, assuming the
+    /// word list and settings are known to an attacker.
+    ///
+    /// Capitalisation doesn't add entropy as it applies to every word. The
+    /// digit adds the entropy of its value and of the word it follows.
+    pub fn entropy(&self) -> f64 {
+        let words = self.words.get() as f64;
+        let mut result = words * (word_list().len() as f64).log2();
+
+        if self.digit {
+            result += (10.0 * words).log2();
+        }
+
+        result
+    }
+}
+
+impl Default for Passphrase {
+    fn default() -> Self {
+        Self {
+            words: config::DEFAULT_PASSPHRASE_WORDS,
+            separator: '-',
+            capitalise: false,
+            digit: false
+        }
+    }
+}
+
+fn word_list() -> Vec<&'static str> {
+    WORDS.lines().collect()
+}
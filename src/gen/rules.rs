@@ -0,0 +1,293 @@
+use super::{Policy, Classes, Error, Result};
+
+use crate::util::{
+    glob::{GlobToken, glob_matches, glob_tokens},
+    record::{Group, Node, Attrs},
+    secret::Secret
+};
+
+use std::fmt;
+
+use std::fmt::Display;
+
+/// The prefix of the metadata keys containing the rules of a policy.
+pub const KEY_PREFIX: &str = "policy.";
+
+/// The maximum number of passwords generated in an attempt to satisfy a
+/// policy.
+const MAX_ATTEMPTS: usize = 10_000;
+
+/// A password policy, restricting the passwords accepted by a site.
+///
+/// Kept in the metadata of a group, and applying to the passwords within it
+/// unless a nested group has a policy of its own. Each rule is stored under its
+/// key prefixed with [`KEY_PREFIX`]:
+///
+/// - `length`: a range of lengths like `8-16`, `8-` or `-16`, or a single one
+/// - `require`: character classes which must appear (see [`Classes::from_str`])
+/// - `forbid`: character classes which must not appear
+/// - `pattern`: a glob pattern which must match the whole password, with the
+///   same syntax as record path elements
+pub struct Rules {
+    pub min_length: usize,
+    pub max_length: Option<usize>,
+    pub require: Classes,
+    pub forbid: Classes,
+    pub pattern: Option<String>
+}
+
+/// A rule broken by a password.
+pub enum Violation {
+    TooShort(usize),
+    TooLong(usize),
+    Lacking(Classes),
+    Containing(Classes),
+    Mismatching(String)
+}
+
+impl Rules {
+    /// Parses the rules in `attrs`.
+    ///
+    /// Returns `None` if `attrs` contains no rules.
+    pub fn from_attrs(attrs: &Attrs) -> Result<Option<Self>> {
+        let mut result = Self::default();
+        let mut found = false;
+
+        for (key, val) in attrs {
+            if let Some(key) = key.strip_prefix(KEY_PREFIX) {
+                result.set(key, val)?;
+                found = true;
+            }
+        }
+
+        Ok(found.then_some(result))
+    }
+
+    /// Returns the policy applying to the passwords in `group`, which is that
+    /// of the nearest group with a policy among `group` and its ancestors.
+    ///
+    /// The name of that group is also returned.
+    pub fn nearest(group: &Node<Group>) -> Result<Option<(Self, String)>> {
+        let mut group = Some(group.clone());
+
+        while let Some(g) = group {
+            let g = g.borrow();
+
+            if let Some(rules) = Self::from_attrs(g.attrs())? {
+                return Ok(Some((rules, g.name().to_owned())));
+            }
+
+            group = g.parent();
+        }
+
+        Ok(None)
+    }
+
+    /// Sets the rule `key` to `val` in `attrs`, or removes it if `val` is
+    /// empty.
+    ///
+    /// `attrs` is left unchanged if the resulting policy is invalid.
+    pub fn set_in(attrs: &mut Attrs, key: &str, val: &str) -> Result<()> {
+        let mut new_attrs = attrs.clone();
+        let full_key = format!("{KEY_PREFIX}{key}");
+
+        if val.is_empty() {
+            new_attrs.remove(&full_key);
+        } else {
+            Self::default().set(key, val)?;
+            new_attrs.insert(full_key, val.to_owned());
+        }
+
+        Self::from_attrs(&new_attrs)?;
+        *attrs = new_attrs;
+
+        Ok(())
+    }
+
+    /// Returns the rules broken by `pw`.
+    pub fn check(&self, pw: &str) -> Vec<Violation> {
+        use Violation::*;
+
+        let mut result = Vec::new();
+        let len = pw.chars().count();
+        let classes = Classes::of(pw);
+
+        if len < self.min_length {
+            result.push(TooShort(self.min_length));
+        }
+
+        if let Some(max) = self.max_length.filter(|max| len > *max) {
+            result.push(TooLong(max));
+        }
+
+        let lacking = self.require.without(classes);
+        let containing = self.forbid.intersection(classes);
+
+        if !lacking.is_empty() {
+            result.push(Lacking(lacking));
+        }
+
+        if !containing.is_empty() {
+            result.push(Containing(containing));
+        }
+
+        let mismatching = self.pattern.as_ref()
+            .filter(|p| !pattern_matches(p, pw));
+
+        if let Some(pat) = mismatching {
+            result.push(Mismatching(pat.clone()));
+        }
+
+        result
+    }
+
+    /// Returns a password generated according to `policy`, adjusted to satisfy
+    /// these rules.
+    ///
+    /// Required classes are added to those of `policy` and forbidden ones are
+    /// removed from them, and the length is brought within the allowed range.
+    /// If there is a pattern, its wildcards are filled with random characters,
+    /// the first '*' taking all those needed to reach the length.
+    pub fn generate(&self, policy: &Policy) -> Result<Secret<String>> {
+        let classes = policy.classes
+            .union(self.require)
+            .without(self.forbid);
+
+        if classes.is_empty() {
+            return Err(Error::NoClasses);
+        }
+
+        let mut length = policy.length.max(self.min_length);
+
+        if let Some(max) = self.max_length {
+            length = length.min(max);
+        }
+
+        let policy = Policy { length, classes, ..*policy };
+
+        for _ in 0..MAX_ATTEMPTS {
+            let pw = match &self.pattern {
+                Some(pat) => fill_pattern(pat, &policy),
+                None => policy.generate()?
+            };
+
+            if self.check(&pw).is_empty() {
+                return Ok(pw);
+            }
+        }
+
+        Err(Error::Unsatisfiable)
+    }
+
+    /// Sets the rule `key` to `val`.
+    fn set(&mut self, key: &str, val: &str) -> Result<()> {
+        let invalid = || Error::InvalidRule(key.to_owned(), val.to_owned());
+
+        match key {
+            "length" => {
+                let parse = |s: &str| s.parse::<usize>().map_err(|_| invalid());
+
+                (self.min_length, self.max_length) = match val.split_once('-') {
+                    Some(("", max)) => (0, Some(parse(max)?)),
+                    Some((min, "")) => (parse(min)?, None),
+                    Some((min, max)) => (parse(min)?, Some(parse(max)?)),
+                    None => (parse(val)?, Some(parse(val)?))
+                };
+
+                if self.max_length.is_some_and(|max| max < self.min_length) {
+                    return Err(invalid());
+                }
+            }
+
+            "require" => self.require = Classes::from_str(val)
+                .ok_or_else(invalid)?,
+            "forbid" => self.forbid = Classes::from_str(val)
+                .ok_or_else(invalid)?,
+            "pattern" => self.pattern = Some(val.to_owned()),
+
+            _ => return Err(invalid())
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Rules {
+    /// Returns rules accepting any password.
+    fn default() -> Self {
+        Self {
+            min_length: 0,
+            max_length: None,
+            require: Classes::NONE,
+            forbid: Classes::NONE,
+            pattern: None
+        }
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Violation::*;
+
+        match self {
+            TooShort(min) =>
+                write!(f, "shorter than {min} characters"),
+            TooLong(max) =>
+                write!(f, "longer than {max} characters"),
+            Lacking(c) =>
+                write!(f, "lacks {}", c.names().join(" and ")),
+            Containing(c) =>
+                write!(f, "contains {}", c.names().join(" and ")),
+            Mismatching(pat) =>
+                write!(f, "does not match '{pat}'")
+        }
+    }
+}
+
+/// Verifies if `pw` matches the glob pattern `pat` of a policy.
+fn pattern_matches(pat: &str, pw: &str) -> bool {
+    glob_matches(pat, pw)
+}
+
+/// Returns a password matching the glob pattern `pat`, with its wildcards
+/// filled with random characters according to `policy`.
+///
+/// The first [`GlobToken::AnyChars`] is filled so that the password is
+/// `policy.length` characters long if possible, and any other one matches no
+/// characters.
+fn fill_pattern(pat: &str, policy: &Policy) -> Secret<String> {
+    use GlobToken::*;
+
+    use rand::Rng;
+    use rand::rngs::OsRng;
+
+    let tokens = glob_tokens(pat).collect::<Vec<_>>();
+    let alphabet = policy.char_sets().concat();
+
+    let fixed_len = tokens.iter()
+        .filter(|t| !matches!(t, AnyChars))
+        .count();
+
+    let mut extra_len = policy.length.saturating_sub(fixed_len);
+    // Sized for characters of any width, so that it is never reallocated.
+    let capacity = (fixed_len + extra_len) * char::MAX.len_utf8();
+    let mut result = Secret::new(String::with_capacity(capacity));
+
+    // As in `Policy::generate`.
+    let push_random = |result: &mut String| {
+        result.push(alphabet[OsRng.gen_range(0..alphabet.len())]);
+    };
+
+    for t in tokens {
+        match t {
+            Char(c) => result.push(c),
+            AnyChar => push_random(&mut result),
+
+            AnyChars => for _ in 0..std::mem::take(&mut extra_len) {
+                push_random(&mut result);
+            }
+        }
+    }
+
+    result
+}
@@ -8,6 +8,10 @@ use crate::util::{
     secret::Secret,
 };
 
+use crate::config;
+
+use crate::audit::strength::{Estimator, Estimate};
+
 use crate::err;
 
 use std::fmt;
@@ -18,22 +22,44 @@ pub enum Error {
     HidingInput(user_io::Error),
     ShowingInput(user_io::Error),
     ReadingInput(user_io::Error),
-    GeneratingKey(key::Error)
+    GeneratingKey(key::Error),
+    WeakGenerated
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Reads a new password and its confirmation, and returns the key generated
+/// from it with `head`.
+///
+/// The strength of the password is displayed, and it is read again if it
+/// scores below [`config::MIN_MASTER_PASSWORD_SCORE`] unless `allow_weak`.
 pub fn confirm_to_key(
     head: &Header,
     prompt_1: &str,
-    prompt_2: &str
+    prompt_2: &str,
+    allow_weak: bool
 ) -> Result<Key> {
+    let estimator = Estimator::new();
+
     loop {
+        let pw = Secret::new(read(prompt_1)?);
+        let estimate = estimator.estimate(&pw);
+
+        print_meter(&estimate);
+
+        if estimate.score() < config::MIN_MASTER_PASSWORD_SCORE && !allow_weak {
+            err!("password is too weak");
+            continue;
+        }
+
         // Instead of comparing the passwords directly (potentially in variable
         // time, and thus enabling side-channel attacks), we compare their
         // hashes in constant time.
         let (key, key_confirm) = (
-            Secret::new(read_to_key(head, prompt_1)?),
+            Secret::new(
+                Key::from_password(pw, head)
+                    .map_err(Error::GeneratingKey)?
+            ),
             Secret::new(read_to_key(head, prompt_2)?)
         );
 
@@ -45,6 +71,51 @@ pub fn confirm_to_key(
     }
 }
 
+/// Displays the strength of a generated password with `entropy` bits, in the
+/// same way as [`confirm_to_key`].
+///
+/// Fails if it scores below [`config::MIN_MASTER_PASSWORD_SCORE`] unless
+/// `allow_weak`.
+pub fn check_generated(entropy: f64, allow_weak: bool) -> Result<()> {
+    let estimate = Estimate::from_entropy(entropy);
+
+    print_meter(&estimate);
+
+    if estimate.score() < config::MIN_MASTER_PASSWORD_SCORE && !allow_weak {
+        return Err(Error::WeakGenerated);
+    }
+
+    Ok(())
+}
+
+/// Displays `pw` and reads it back from the user until it is entered
+/// correctly, so that it is known to have been memorised or written down.
+///
+/// Returns the key generated from `pw` with `head`.
+pub fn show_confirm_to_key(
+    head: &Header,
+    pw: Secret<String>,
+    prompt: &str
+) -> Result<Key> {
+    eprintln!("{}", *pw);
+
+    let key = Secret::new(
+        Key::from_password(pw, head)
+            .map_err(Error::GeneratingKey)?
+    );
+
+    loop {
+        // As in `confirm_to_key`.
+        let key_confirm = Secret::new(read_to_key(head, prompt)?);
+
+        if *key == *key_confirm {
+            break Ok(key.into_inner());
+        } else {
+            err!("passwords do not match");
+        }
+    }
+}
+
 /// XXX: reads pw and generates key with `head`
 pub fn read_to_key(head: &Header, prompt: &str) -> Result<Key> {
     let pw = Secret::new(read(prompt)?);
@@ -85,6 +156,32 @@ pub fn read(prompt: &str) -> Result<String> {
     }
 }
 
+/// Displays a meter of the strength of a password from its `estimate`, and the
+/// patterns weakening it.
+fn print_meter(estimate: &Estimate) {
+    use crate::util::user_io::Style;
+
+    // Two cells per point of score.
+    let score = estimate.score() as usize;
+    let meter = format!("[{:-<8}]", "#".repeat(score * 2));
+
+    let meter = match estimate.score() {
+        s if s < config::MIN_MASTER_PASSWORD_SCORE => meter.as_error(),
+        s if s < config::MIN_PASSWORD_SCORE => meter.as_warning(),
+        _ => meter.as_notice()
+    };
+
+    let reasons = estimate.patterns().iter()
+        .map(|p| format!(", {p}"))
+        .collect::<String>();
+
+    eprintln!(
+        "Strength: {meter} {}, about {:.0} bits{reasons}",
+        estimate.label(),
+        estimate.entropy()
+    );
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use Error::*;
@@ -93,7 +190,8 @@ impl Display for Error {
             HidingInput(e)   => write!(f, "cannot hide input: {e}"),
             ShowingInput(e)  => write!(f, "cannot show input: {e}"),
             ReadingInput(e)  => write!(f, "{e}"),
-            GeneratingKey(e) => write!(f, "{e}")
+            GeneratingKey(e) => write!(f, "{e}"),
+            WeakGenerated    => write!(f, "generated password is too weak")
         }
     }
 }
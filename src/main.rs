@@ -12,6 +12,8 @@ mod serial;
 mod find;
 mod tui;
 mod output;
+mod audit;
+mod gen;
 mod util;
 
 use error::{Error, Result};
@@ -25,7 +27,6 @@ use util::{
 
 use util::{
     file::{SafePath, Mode},
-    record::Record,
     secret::Secret
 };
 
@@ -33,9 +34,12 @@ use util::crypt::{CryptCtx, Header, Key};
 
 use std::{
     process::ExitCode,
-    fs::File
+    fs::File,
+    io
 };
 
+use std::io::IsTerminal;
+
 fn main() -> ExitCode {
     let result = Cmd::from_env()
         .map_err(Error::from)
@@ -59,6 +63,17 @@ impl Cmd {
         match self {
             ShowUsage(usg) => println!("{usg}"),
             ShowVersion(ver) => println!("{ver}"),
+            Generate(policy) => with_secured_mem(|| {
+                println!("{}", *policy.generate()?);
+                Ok(())
+            })?,
+            GeneratePhrase(phrase) => with_secured_mem(|| {
+                info!("Estimated entropy: about {:.0} bits", phrase.entropy());
+                println!("{}", *phrase.generate());
+                Ok(())
+            })?,
+            ClearClip => output::clear_clip()?,
+            HoldClip(opts) => with_secured_mem(|| output::hold_clip(opts))?,
             HandleFile(cmd, path) => cmd.exec(path)?,
         }
 
@@ -84,7 +99,9 @@ impl FileCmd {
 
 impl ReadCmd {
     fn exec(self, path: SafePath) -> Result<()> {
-        use output::{PrintTarget, ClipTarget};
+        use output::{PrintTarget, ClipTarget, QrTarget};
+        use output::{SearchTarget, ExtractTarget};
+        use output::{ReuseAudit, StrengthAudit, BreachAudit};
         use ReadCmd::*;
 
         let data = Secret::new({
@@ -102,30 +119,55 @@ impl ReadCmd {
         });
 
         match self {
-            Show(paths, mk) => PrintTarget::new(paths, mk)
-                .print_values(&data),
+            Show { paths, mk, attachments, time, raw, format, report } => {
+                output::check_show_output(raw)?;
+
+                PrintTarget::new(paths, mk, report, format)
+                    .print_values(&data, attachments, time);
+            }
 
-            Clip(path, mk, time) => {
-                // It doesn't matter if this is the parent or child process,
-                // because it is about to exit without further effects.
-                let (_, result) = ClipTarget::new(path, mk, time)
-                    .clip(&data);
+            Clip(path, mk, fields, opts, report) => {
+                let field_count = fields.len();
+                let mut child = ClipTarget::new(path, mk, fields, opts, report)
+                    .clip(&data)?;
 
-                result?;
+                if field_count > 1 && io::stdin().is_terminal() {
+                    output::skip_on_enter(&mut child, field_count)?;
+                }
             }
 
-            List(opt_paths, mk) => match opt_paths {
-                Some(paths) => PrintTarget::new(paths, mk)
+            Qr(path, mk, time, report) => QrTarget::new(path, mk, time, report)
+                .show(&data)?,
+
+            Search(pat, mk, values, format) =>
+                SearchTarget::new(pat, mk, values, format)
+                    .print_matches(&data)?,
+
+            Extract(path, mk, dest) => ExtractTarget::new(path, mk, dest)
+                .extract(&data)?,
+
+            List(opt_paths, mk, format, report) => match opt_paths {
+                Some(paths) => PrintTarget::new(paths, mk, report, format)
                     .print_lists(&data),
-                None => println!("{}", Record::display_list(&data))
+                None => output::print_root_list(&data, format)?
             }
 
-            Tree(opt_paths, mk) => match opt_paths {
-                Some(paths) => PrintTarget::new(paths, mk)
+            Tree(opt_paths, mk, format, report) => match opt_paths {
+                Some(paths) => PrintTarget::new(paths, mk, report, format)
                     .print_trees(&data),
-                None => println!("{}", Record::display_tree(&data))
+                None => output::print_root_tree(&data, format)?
             }
 
+            AuditReuse(default_only, format) =>
+                ReuseAudit::new(default_only, format)
+                    .print_report(&data)?,
+
+            AuditStrength(format) => StrengthAudit::new(format)
+                .print_report(&data)?,
+
+            AuditBreached(source, format) => BreachAudit::new(source, format)
+                .print_report(&data)?,
+
             // Already handled.
             Export => unreachable!()
         }
@@ -138,11 +180,10 @@ impl ChangeCmd {
     fn exec(self, path: SafePath) -> Result<()> {
         use backup::Error::File as RecoverError;
         use ChangeCmd::*;
-        use tui::{Tui, Status};
+        use tui::Tui;
 
         let (mut file, path) = open(Mode::ReadWrite, path)?;
         let (serial, pw) = decrypt(&mut file)?;
-        let mut proc_is_child = false;
 
         if let Err(e) = path.make_backup() {
             return Err(Error::MakingBackup(e, path));
@@ -161,10 +202,6 @@ impl ChangeCmd {
                     // catch ctrl-c and exit cleanly
                     let result = tui.run(&data);
 
-                    if tui.status() == Status::Clipped {
-                        proc_is_child = true;
-                    }
-
                     // This code won't be executed if `result` is `Err`.
                     if tui.should_save_data() {
                         let new_serial = Secret::new(
@@ -181,15 +218,11 @@ impl ChangeCmd {
                     result
                 }
 
-                ChangePassword => {
+                ChangePassword(new_pw) => {
                     drop(pw);   // Old password unneeded if we are changing it.
 
                     over_encrypt(&serial, file, |head| {
-                        input_pw::confirm_to_key(
-                            head,
-                            "New password: ",
-                            "Confirm password: "
-                        )
+                        new_pw_to_key(head, new_pw, "New password: ")
                     })?;
 
                     Ok(())
@@ -197,11 +230,6 @@ impl ChangeCmd {
             }
         }();
 
-        // The parent process will take care of the backup.
-        if proc_is_child {
-            return result;
-        }
-
         match &result {
             Ok(_) => if let Err(e) = path.remove_backup() {
                 Error::RemovingBackup(e, path).warn_full();
@@ -224,12 +252,12 @@ impl CreateCmd {
 
         // TODO: use `try` blocks once available
         let result = || -> Result<()> {
-            let serial = match self {
-                CreateEmpty(root_name) => {
-                    Secret::new(serial::new_empty(root_name))
+            let (serial, new_pw) = match self {
+                CreateEmpty(root_name, new_pw) => {
+                    (Secret::new(serial::new_empty(root_name)), new_pw)
                 }
 
-                Import => {
+                Import(new_pw) => {
                     let input = Secret::new(
                         user_io::read_stdin()
                             .map_err(Error::ReadingStdin)?
@@ -238,16 +266,12 @@ impl CreateCmd {
                     serial::validate(&input)
                         .map_err(Error::InputSerial)?;
 
-                    input
+                    (input, new_pw)
                 }
             };
 
             over_encrypt(serial.as_bytes(), file, |head| {
-                input_pw::confirm_to_key(
-                    head,
-                    "Password: ",
-                    "Confirm password: "
-                )
+                new_pw_to_key(head, new_pw, "Password: ")
             })
         }();
 
@@ -261,6 +285,38 @@ impl CreateCmd {
     }
 }
 
+/// Returns the key of a new password for a pass file, chosen as specified by
+/// `new_pw`. `prompt` is used to read the password if it isn't generated.
+fn new_pw_to_key(
+    head: &Header,
+    new_pw: NewPassword,
+    prompt: &str
+) -> input_pw::Result<Key> {
+    match new_pw.phrase {
+        Some(phrase) => {
+            input_pw::check_generated(phrase.entropy(), new_pw.allow_weak)?;
+
+            info!(
+                "The password is the following passphrase (about {:.0} bits):",
+                phrase.entropy()
+            );
+
+            input_pw::show_confirm_to_key(
+                head,
+                phrase.generate(),
+                "Confirm password: "
+            )
+        }
+
+        None => input_pw::confirm_to_key(
+            head,
+            prompt,
+            "Confirm password: ",
+            new_pw.allow_weak
+        )
+    }
+}
+
 /// Runs `op` in a context where the process address space is secured by
 /// [`proc::secure_mem`].
 ///
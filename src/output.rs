@@ -1,170 +1,1085 @@
+use crate::{audit, config, find, env, info, warn, input};
+
 use crate::{
     error::{Error, Result},
     find::{RecordPath, MatchKind}
 };
 
-use crate::util::{clip, proc};
+use crate::util::{clip, proc, file, user_io, qr};
 
 use crate::util::{
-    record::{Record, Node},
-    proc::Process
+    record::{Record, Item, Value, Node},
+    secret::Secret,
+    proc::Pid,
+    json::{self, JsonWriter}
 };
 
-use std::fmt::Display;
+use std::io;
+
+use std::{
+    path::PathBuf,
+    time::Duration,
+    rc::Rc,
+    process::{Command, Child, Stdio}
+};
 
-use std::time::Duration;
+/// How records and reports are printed.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Text for users.
+    #[default]
+    Text,
+    /// A single JSON document for scripts, with full paths and record kinds.
+    Json
+}
 
 /// XXX: several paths
 pub struct PrintTarget {
     paths: Vec<RecordPath>,
-    mk: MatchKind
+    mk: MatchKind,
+    /// Whether to report the full path each target resolves to.
+    report: bool,
+    format: Format
 }
 
 /// XXX: single paths
 pub struct ClipTarget {
     path: RecordPath,
     mk: MatchKind,
-    time: Duration
+    /// The items in the target group to clip in turn, or empty to clip the
+    /// target item.
+    fields: Vec<String>,
+    opts: clip::HoldOpts,
+    /// Whether to report the full path the target resolves to.
+    report: bool
 }
 
-/// a result-like type that carries extra information on whether the process is
-/// a child or a parent, if it was forked
-///
-/// This allows us to track what kind of process this is, even if the result is
-/// an `Err`.
-///
-/// if .0 is None, then the process was not forked
-/// if .0 is Some(p), then p determines whether or not process is forked
-pub type ResultForked = (Option<Process>, Result<()>);
+/// XXX: single paths
+pub struct QrTarget {
+    path: RecordPath,
+    mk: MatchKind,
+    /// How long to display the code.
+    time: Duration,
+    /// Whether to report the full path the target resolves to.
+    report: bool
+}
+
+/// XXX: single paths
+pub struct ExtractTarget {
+    path: RecordPath,
+    mk: MatchKind,
+    dest: PathBuf
+}
+
+/// XXX: searches all records
+pub struct SearchTarget {
+    pat: String,
+    mk: MatchKind,
+    values: bool,
+    format: Format
+}
+
+/// XXX: audits all items
+pub struct ReuseAudit {
+    default_only: bool,
+    format: Format
+}
+
+/// XXX: audits all passwords
+pub struct StrengthAudit {
+    format: Format
+}
+
+/// XXX: audits all passwords
+pub struct BreachAudit {
+    /// The local copy of the breached password hashes.
+    source: PathBuf,
+    format: Format
+}
+
+/// The depth to which the members of groups are printed.
+#[derive(Clone, Copy)]
+enum Layout {
+    /// Only direct members.
+    List,
+    /// Members at any depth.
+    Tree
+}
 
 impl PrintTarget {
-    pub fn new(paths: Vec<RecordPath>, mk: MatchKind) -> Self {
-        Self { paths, mk }
+    pub fn new(
+        paths: Vec<RecordPath>,
+        mk: MatchKind,
+        report: bool,
+        format: Format
+    ) -> Self {
+        Self { paths, mk, report, format }
     }
 
-    pub fn print_values(self, data: &Node<Record>) {
-        for p in self.paths {
-            match p.find_item_or_default_in(data, self.mk) {
-                Ok(item) => println!("{}", item.borrow().value()),
-                Err(e) => Error::from(e).print_full()
+    /// Prints the value of each target item, including every item matched by
+    /// glob patterns.
+    ///
+    /// Attachments are written as raw bytes (or Base64 in JSON) if
+    /// `attachments` is true, and are refused otherwise. If `time` is set and
+    /// standard output is a terminal, text values are erased from it once
+    /// `time` runs out or a key is pressed, and attachments are always refused.
+    pub fn print_values(
+        self,
+        data: &Node<Record>,
+        attachments: bool,
+        time: Option<Duration>
+    ) {
+        use io::IsTerminal;
+
+        let items = self.resolve_items(data);
+
+        if self.format == Format::Json {
+            if let Err(e) = write_values_json(&items, attachments) {
+                Error::WritingStdout(e).print_full();
+            }
+
+            return;
+        }
+
+        // Values written elsewhere cannot be erased.
+        let time = time.filter(|_| io::stdout().is_terminal());
+
+        // Sized in advance so that it is never reallocated, which would leave
+        // unerased copies of the values behind. See `push_value`.
+        let len = items.iter()
+            .filter_map(|(_, i)| i.borrow().value().as_text().map(str::len))
+            .map(|l| l + 1)
+            .sum();
+
+        let mut shown = Secret::new(String::with_capacity(len));
+
+        for (_, item) in items {
+            let result = match time {
+                Some(_) => push_value(&mut shown, &item.borrow()),
+                None => print_value(&item.borrow(), attachments)
+            };
+
+            if let Err(e) = result {
+                e.print_full();
+            }
+        }
+
+        if let Some(time) = time.filter(|_| !shown.is_empty()) {
+            info!("Press any key to erase the values");
+
+            if let Err(e) = user_io::display_timed(&shown, time) {
+                Error::WritingStdout(e).print_full();
             }
         }
     }
 
     pub fn print_lists(self, data: &Node<Record>) {
-        print_each_spaced(self.paths, |p| {
-            let rec = p.find_in(data, self.mk)?;
-
-            Ok(Record::display_list(&rec))
-        })
+        self.print_records(data, Layout::List)
     }
 
     pub fn print_trees(self, data: &Node<Record>) {
-        print_each_spaced(self.paths, |p| {
-            let rec = p.find_in(data, self.mk)?;
+        self.print_records(data, Layout::Tree)
+    }
+
+    /// Returns each target item along with its full path, including every item
+    /// matched by glob patterns.
+    ///
+    /// If an item cannot be found, the error is printed and it is skipped.
+    fn resolve_items(
+        &self,
+        data: &Node<Record>
+    ) -> Vec<(RecordPath, Node<Item>)> {
+        let mut result = Vec::new();
+
+        for (pat, path, rec) in self.resolve_records(data) {
+            match find::resolved_item_or_default(&rec, path) {
+                Ok((path, item)) => {
+                    if self.report {
+                        report_resolved(&pat, &path);
+                    }
+
+                    result.push((path, item));
+                }
+
+                Err(e) => Error::from(e).print_full()
+            }
+        }
+
+        result
+    }
+
+    /// Returns each target record along with the target matching it and its
+    /// full path.
+    ///
+    /// If a target cannot be found, the error is printed and it is skipped.
+    fn resolve_records(
+        &self,
+        data: &Node<Record>
+    ) -> Vec<(RecordPath, RecordPath, Node<Record>)> {
+        let mut result = Vec::new();
+
+        for p in &self.paths {
+            let found = p.find_choosing(
+                data, |p, r| p.resolve_all_in(r, self.mk), choose_record
+            );
+
+            match found {
+                Ok(recs) => result.extend(recs.into_iter()
+                    .map(|(path, rec)| (p.clone(), path, rec))),
+                Err(e) => Error::from(e).print_full()
+            }
+        }
+
+        result
+    }
 
-            Ok(Record::display_tree(&rec))
-        })
+    /// Prints each target record with `layout`, separated with empty lines in
+    /// text.
+    fn print_records(self, data: &Node<Record>, layout: Layout) {
+        let recs = self.resolve_records(data);
+
+        if self.format == Format::Json {
+            let recs = recs.into_iter()
+                .map(|(pat, path, rec)| {
+                    if self.report {
+                        report_resolved(&pat, &path);
+                    }
+
+                    (path, rec)
+                })
+                .collect::<Vec<_>>();
+
+            if let Err(e) = write_records_json(&recs, layout) {
+                Error::WritingStdout(e).print_full();
+            }
+
+            return;
+        }
+
+        for (i, (pat, path, rec)) in recs.into_iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+
+            if self.report {
+                report_resolved(&pat, &path);
+            }
+
+            match layout {
+                Layout::List => println!("{}", Record::display_list(&rec)),
+                Layout::Tree => println!("{}", Record::display_tree(&rec))
+            }
+        }
     }
 }
 
 impl ClipTarget {
-    pub fn new(path: RecordPath, mk: MatchKind, time: Duration) -> Self {
-        Self { path, mk, time }
+    pub fn new(
+        path: RecordPath,
+        mk: MatchKind,
+        fields: Vec<String>,
+        opts: clip::HoldOpts,
+        report: bool
+    ) -> Self {
+        Self { path, mk, fields, opts, report }
     }
 
     /// Finds the target in `data` and copies it to the clipboard.
     ///
-    /// Forks the process into a parent a child, the latter of which is
-    /// responsible for preserving the clipboard. See [`clip_timed`] for more
-    /// details.
-    pub fn clip(self, data: &Node<Record>) -> ResultForked {
-        let item_result = self.path
-            .find_item_or_default_in(data, self.mk);
-
-        let item = match item_result {
-            Ok(i) => i,
-            Err(e) => return (None, Err(e.into()))
-        };
+    /// Starts a process responsible for preserving the clipboard, and returns
+    /// it. See [`clip_timed`] for more details.
+    pub fn clip(self, data: &Node<Record>) -> Result<Child> {
+        let items = self.resolve(data)?;
+
+        let items = items.iter()
+            .map(|i| i.borrow())
+            .collect::<Vec<_>>();
+
+        let mut values = Vec::with_capacity(items.len());
+
+        for item in &items {
+            let Some(value) = item.value().as_text() else {
+                let name = item.name().to_owned();
+                return Err(Error::UnexpectedAttachment(name));
+            };
+
+            values.push(value);
+        }
+
+        clip_timed(&values, self.opts)
+    }
+
+    /// Returns the items to clip in turn.
+    fn resolve(&self, data: &Node<Record>) -> Result<Vec<Node<Item>>> {
+        if self.fields.is_empty() {
+            let (path, item) = self.path.find_choosing(
+                data,
+                |p, r| p.resolve_item_or_default_in(r, self.mk),
+                choose_record,
+            )?;
+
+            if self.report {
+                report_resolved(&self.path, &path);
+            }
+
+            return Ok(vec![item]);
+        }
+
+        let group = self.path.find_choosing(
+            data, |p, r| p.find_in(r, self.mk), choose_record
+        )?;
+
+        self.fields.iter()
+            .map(|f| {
+                let item = RecordPath::from_names([f.as_str()]).find_choosing(
+                    &group, |p, r| p.find_item_in(r, self.mk), choose_record
+                )?;
+
+                if self.report {
+                    let mut pat = self.path.clone();
+                    let path = RecordPath::of(&Record::Item(Rc::clone(&item)));
+
+                    pat.push(f);
+                    report_resolved(&pat, &path);
+                }
+
+                Ok(item)
+            })
+            .collect()
+    }
+}
+
+impl QrTarget {
+    pub fn new(
+        path: RecordPath,
+        mk: MatchKind,
+        time: Duration,
+        report: bool
+    ) -> Self {
+        Self { path, mk, time, report }
+    }
+
+    /// Finds the target item in `data` and displays its value as a QR code on
+    /// the terminal, until `self.time` runs out or a key is pressed. The code
+    /// is then erased.
+    ///
+    /// Fails if standard output is not a terminal.
+    pub fn show(self, data: &Node<Record>) -> Result<()> {
+        use io::IsTerminal;
+
+        if !io::stdout().is_terminal() {
+            return Err(Error::StdoutNotTerminal);
+        }
+
+        let (path, item) = self.path.find_choosing(
+            data, |p, r| p.resolve_item_or_default_in(r, self.mk), choose_record
+        )?;
+
+        if self.report {
+            report_resolved(&self.path, &path);
+        }
 
         let item = item.borrow();
-        let value = item.value();
 
-        clip_timed(value, self.time)
+        let code = qr::QrCode::encode(item.value().as_bytes())
+            .ok_or_else(|| Error::EncodingQrCode(item.name().to_owned()))?;
+
+        info!("Press any key to clear the code");
+
+        user_io::display_timed(&code.render(), self.time)
+            .map_err(Error::WritingStdout)
+    }
+}
+
+impl ExtractTarget {
+    pub fn new(path: RecordPath, mk: MatchKind, dest: PathBuf) -> Self {
+        Self { path, mk, dest }
+    }
+
+    /// Finds the target item in `data` and writes its value to a new file.
+    ///
+    /// The file is only accessible by its owner. See [`file::write_private`].
+    pub fn extract(self, data: &Node<Record>) -> Result<()> {
+        let item = self.path.find_choosing(
+            data, |p, r| p.find_item_in(r, self.mk), choose_record
+        )?;
+        let item = item.borrow();
+
+        file::write_private(&self.dest, item.value().as_bytes())
+            .map_err(|e| Error::WritingAttachment(e, self.dest))
+    }
+}
+
+impl SearchTarget {
+    pub fn new(
+        pat: String,
+        mk: MatchKind,
+        values: bool,
+        format: Format
+    ) -> Self {
+        Self { pat, mk, values, format }
+    }
+
+    /// Prints the full path of each matching record in `data` on its own line,
+    /// or along with its kind in JSON.
+    ///
+    /// See [`find::search_in`].
+    pub fn print_matches(self, data: &Node<Record>) -> Result<()> {
+        let paths = find::search_in(data, &self.pat, self.mk, self.values)?;
+
+        if self.format == Format::Json {
+            return write_json(|w| {
+                w.begin_array()?;
+
+                for p in &paths {
+                    // The paths were just found, so they exist.
+                    let rec = p.find_in(data, MatchKind::Exact).unwrap();
+
+                    w.begin_object()?;
+                    w.member("path", &p.to_string())?;
+                    w.member("kind", kind_of(&rec.borrow()))?;
+                    w.end_object()?;
+                }
+
+                w.end_array()
+            });
+        }
+
+        for p in paths {
+            println!("{p}");
+        }
+
+        Ok(())
+    }
+}
+
+impl ReuseAudit {
+    pub fn new(default_only: bool, format: Format) -> Self {
+        Self { default_only, format }
+    }
+
+    /// Prints the paths of the items in `data` sharing the same value, with
+    /// each group of paths separated by an empty line, or as an array of arrays
+    /// in JSON. Values are never displayed.
+    ///
+    /// See [`audit::find_reused`].
+    pub fn print_report(self, data: &Node<Record>) -> Result<()> {
+        let groups = audit::find_reused(data, self.default_only);
+
+        if groups.is_empty() {
+            info!("No reused values found");
+        }
+
+        if self.format == Format::Json {
+            return write_json(|w| {
+                w.begin_array()?;
+
+                for group in &groups {
+                    w.begin_array()?;
+
+                    for p in group {
+                        w.string(&p.to_string())?;
+                    }
+
+                    w.end_array()?;
+                }
+
+                w.end_array()
+            });
+        }
+
+        for (i, group) in groups.iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+
+            for p in group {
+                println!("{p}");
+            }
+        }
+
+        Ok(())
     }
 }
 
-/// Copies `text` to the primary clipboard, and clears it after `time`.
+impl StrengthAudit {
+    pub fn new(format: Format) -> Self {
+        Self { format }
+    }
+
+    /// Prints the path of each weak password in `data` on its own line, with
+    /// its estimated strength and the patterns that weaken it. Passwords are
+    /// never displayed.
+    ///
+    /// See [`audit::find_weak`].
+    pub fn print_report(self, data: &Node<Record>) -> Result<()> {
+        let weak = audit::find_weak(data);
+
+        if weak.is_empty() {
+            info!("No weak passwords found");
+        }
+
+        if self.format == Format::Json {
+            return write_json(|w| {
+                w.begin_array()?;
+
+                for (path, estimate) in &weak {
+                    w.begin_object()?;
+                    w.member("path", &path.to_string())?;
+                    w.member("strength", estimate.label())?;
+                    w.key("entropy")?;
+                    w.number(format_args!("{:.1}", estimate.entropy()))?;
+                    w.key("patterns")?;
+                    w.begin_array()?;
+
+                    for p in estimate.patterns() {
+                        w.string(&p.to_string())?;
+                    }
+
+                    w.end_array()?;
+                    w.end_object()?;
+                }
+
+                w.end_array()
+            });
+        }
+
+        for (path, estimate) in weak {
+            let reasons = match estimate.patterns() {
+                [] => String::from("too short"),
+
+                patterns => patterns.iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+
+            println!(
+                "{path}: {}, about {:.0} bits ({reasons})",
+                estimate.label(),
+                estimate.entropy()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl BreachAudit {
+    pub fn new(source: PathBuf, format: Format) -> Self {
+        Self { source, format }
+    }
+
+    /// Prints the path of each password in `data` seen in breaches on its own
+    /// line, with the number of times it was seen. Passwords are never
+    /// displayed, and no network access is involved.
+    ///
+    /// See [`audit::find_breached`].
+    pub fn print_report(self, data: &Node<Record>) -> Result<()> {
+        let breached = audit::hibp::Breaches::open(&self.source)
+            .and_then(|mut b| audit::find_breached(data, &mut b))
+            .map_err(|e| Error::ReadingBreaches(e, self.source))?;
+
+        if breached.is_empty() {
+            info!("No breached passwords found");
+        }
+
+        if self.format == Format::Json {
+            return write_json(|w| {
+                w.begin_array()?;
+
+                for (path, count) in &breached {
+                    w.begin_object()?;
+                    w.member("path", &path.to_string())?;
+                    w.key("count")?;
+                    w.number(count)?;
+                    w.end_object()?;
+                }
+
+                w.end_array()
+            });
+        }
+
+        for (path, count) in breached {
+            println!("{path}: seen {count} times in breaches");
+        }
+
+        Ok(())
+    }
+}
+
+/// Verifies that item values may be displayed on standard output.
+///
+/// If standard output is not a terminal, values may end up in files or logs,
+/// so this fails or prints a warning according to
+/// [`config::REFUSE_NON_TERMINAL_SHOW`], unless `raw` is true.
+pub fn check_show_output(raw: bool) -> Result<()> {
+    use io::IsTerminal;
+
+    if raw || io::stdout().is_terminal() {
+        return Ok(());
+    }
+
+    if config::REFUSE_NON_TERMINAL_SHOW {
+        return Err(Error::RefusingNonTerminal);
+    }
+
+    warn!("displaying secrets while stdout is not a terminal");
+
+    Ok(())
+}
+
+/// Copies each of `texts` in turn to the clipboard, and clears it as specified
+/// by `opts`. See [`clip::Clipboard::hold`].
 ///
-/// This operation is non-blocking for the calling process, as an identical
-/// child process is started to preserve the clipboard as long as necessary
-/// before continuing execution. The child process' memory is secured using
-/// [`proc::secure_mem`].
+/// This operation is non-blocking, as a holder process is started to preserve
+/// the clipboard as long as necessary. It is a new instance of the program in a
+/// hidden mode (see [`hold_clip`]), which receives `texts` through a pipe and
+/// is the only process accessing the clipboard.
 ///
-/// Returns a value indicating whether the current process is the child or
-/// parent. An expected usage pattern is to immediately end the child process
-/// without it performing any IO.
-pub fn clip_timed(text: &str, time: Duration) -> ResultForked {
+/// Returns the holder process.
+pub fn clip_timed(texts: &[&str], opts: clip::HoldOpts) -> Result<Child> {
+    use std::os::unix::process::CommandExt;
+
+    let exe = std::env::current_exe().map_err(Error::StartingProcess)?;
+    let mut command = Command::new(exe);
+
+    command.args(holder_args(opts))
+        .stdin(Stdio::piped());
+
+    // Signals sent to the holder before it catches them would terminate it,
+    // leaving the clipboard uncleared. They stay pending until then instead.
+    //
+    // SAFETY: Blocking signals is async-signal-safe.
+    unsafe {
+        command.pre_exec(|| clip::block_signals().map_err(io::Error::from));
+    }
+
+    let mut child = command.spawn()
+        .map_err(Error::StartingProcess)?;
+
+    // Closes the pipe once dropped. It was requested, so it exists.
+    let mut pipe = child.stdin.take().unwrap();
+
+    write_texts(&mut pipe, texts)
+        .map_err(Error::StartingProcess)?;
+
+    Ok(child)
+}
+
+/// Holds the texts received on standard input in the clipboard as specified by
+/// `opts`. Done by the holder processes started by [`clip_timed`], whose
+/// memory must be secured using [`proc::secure_mem`] beforehand.
+pub fn hold_clip(opts: clip::HoldOpts) -> Result<()> {
     use clip::Clipboard;
 
-    // SAFETY: Forking the process is completely safe because ours is
-    // single-threaded.
-    let proc_result = unsafe {
-        // Since we will not modify memory allocated by the parent process, the
-        // kernel should be able to apply COW optimisations, allowing for a low
-        // performance penalty.
-        proc::fork()
-    };
+    // The starting process may signal this one as soon as it exists.
+    clip::catch_signals();
+
+    let (data, lens) = read_texts()
+        .map_err(Error::ReadingStdin)?;
+
+    let mut texts = Vec::with_capacity(lens.len());
+    let mut rest = data.as_slice();
+
+    for len in lens {
+        let (text, next) = rest.split_at(len);
+
+        texts.push(std::str::from_utf8(text).map_err(|_| {
+            Error::ReadingStdin(io::ErrorKind::InvalidData.into())
+        })?);
+
+        rest = next;
+    }
 
-    let proc = match proc_result {
-        Ok(p) => p,
-        Err(e) => return (None, Err(Error::StartingProcess(e)))
+    let holders_dir = env::clip_holders_dir();
+
+    Clipboard::new(holders_dir.as_deref(), opts.terminal)?
+        .hold(&texts, opts)?;
+
+    Ok(())
+}
+
+/// Stops the processes holding items in the clipboard, which clear it. See
+/// [`clip::holders::stop_all`].
+pub fn clear_clip() -> Result<()> {
+    let stopped = match env::clip_holders_dir() {
+        Some(dir) => clip::holders::stop_all(&dir)
+            .map_err(Error::StoppingProcesses)?,
+        None => 0
     };
 
-    if proc == Process::Child {
-        // TODO: use `try` blocks once available
-        let result = (|| -> Result<()> {
-            // The child process does not inherit the parent's memory
-            // protections, so they must be reapplied.
-            proc::secure_mem()
-                .map_err(Error::SecuringMemory)?;
+    if stopped == 0 {
+        info!("No items held in the clipboard");
+    }
+
+    Ok(())
+}
+
+/// Makes the clipboard process `child` holding `count` texts move on to the
+/// next one each time the user enters a line, until it holds the last one or
+/// exits.
+pub fn skip_on_enter(child: &mut Child, count: usize) -> Result<()> {
+    /// How often to check whether `child` exited.
+    const POLL_TIME: Duration = Duration::from_millis(200);
+
+    info!("Press Enter to skip to the next field");
+
+    for _ in 1..count {
+        loop {
+            if child.try_wait().map_err(Error::ControllingProcess)?.is_some() {
+                return Ok(());
+            }
+
+            if user_io::wait_stdin(POLL_TIME).map_err(Error::ReadingInput)? {
+                break;
+            }
+        }
+
+        let read_len = io::stdin()
+            .read_line(&mut String::new())
+            .map_err(Error::ReadingInput)?;
+
+        // The user closed the stream.
+        if read_len == 0 {
+            break;
+        }
+
+        // PIDs fit in an `i32`.
+        let pid = Pid::from_raw(child.id() as i32);
+
+        proc::send_signal(pid, clip::SKIP_SIGNAL)
+            .map_err(|e| Error::ControllingProcess(e.into()))?;
+    }
+
+    Ok(())
+}
+
+/// Returns the arguments starting a holder process with `opts`.
+fn holder_args(opts: clip::HoldOpts) -> Vec<String> {
+    let mut result = vec![
+        format!("--{}", env::CLIP_HOLDER_OPT),
+        format!("--duration={}", opts.time.as_secs()),
+        format!("--selection={}", opts.selection)
+    ];
+
+    if let Some(n) = opts.pastes {
+        result.push(format!("--pastes={n}"));
+    }
+
+    if opts.restore {
+        result.push(String::from("--restore"));
+    }
+
+    if opts.terminal {
+        result.push(String::from("--osc52"));
+    }
+
+    result
+}
+
+/// Writes `texts` to the holder process through `pipe`.
+///
+/// A line containing the length in bytes of each text separated by spaces is
+/// written first, followed by the texts themselves. See [`read_texts`].
+fn write_texts<W: io::Write>(pipe: &mut W, texts: &[&str]) -> io::Result<()> {
+    let lens = texts.iter()
+        .map(|t| t.len().to_string())
+        .collect::<Vec<_>>();
+
+    writeln!(pipe, "{}", lens.join(" "))?;
+
+    for t in texts {
+        pipe.write_all(t.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Reads the texts written by [`write_texts`] from standard input.
+///
+/// Returns their concatenation and their lengths. Standard input is read
+/// without buffering, so that the texts are never copied outside the returned
+/// [`Secret`].
+fn read_texts() -> io::Result<(Secret<Vec<u8>>, Vec<usize>)> {
+    use io::Read;
+
+    use std::fs::File;
+    use std::os::fd::AsFd;
+
+    let invalid = || io::Error::from(io::ErrorKind::InvalidData);
 
-            Clipboard::new()?
-                .hold(text, time)?;
+    let mut stdin = File::from(io::stdin().as_fd().try_clone_to_owned()?);
+    let mut header = Vec::new();
+    let mut byte = [0];
 
-            Ok(())
-        })();
+    loop {
+        stdin.read_exact(&mut byte)?;
 
-        (Some(proc), result.map_err(Error::from))
-    } else {
-        (Some(proc), Ok(()))
+        match byte[0] {
+            b'\n' => break,
+            b => header.push(b)
+        }
     }
+
+    let lens = std::str::from_utf8(&header)
+        .map_err(|_| invalid())?
+        .split_whitespace()
+        .map(|l| l.parse().map_err(|_| invalid()))
+        .collect::<io::Result<Vec<usize>>>()?;
+
+    let mut data = Secret::new(vec![0; lens.iter().sum()]);
+    stdin.read_exact(&mut data)?;
+
+    Ok((data, lens))
 }
 
-/// Applies 'f' to each element of `paths` and prints the result separated with
-/// empty lines.
+/// Prints the value of `item` to standard output.
 ///
-/// If `f` returns an error, it is printed and execution continues
-fn print_each_spaced<F, D>(paths: Vec<RecordPath>, f: F)
+/// Fails if `item` is an attachment and `attachments` is false.
+fn print_value(item: &Item, attachments: bool) -> Result<()> {
+    use std::io::Write;
+
+    match item.value() {
+        Value::Text(t) => println!("{t}"),
+
+        Value::Binary(b) if attachments => io::stdout()
+            .write_all(b)
+            .map_err(Error::WritingStdout)?,
+
+        Value::Binary(_) => return Err(Error::UnexpectedAttachment(
+            item.name().to_owned()
+        ))
+    }
+
+    Ok(())
+}
+
+/// Appends the value of `item` to `dest` with a newline, to be displayed
+/// later.
+///
+/// Fails if `item` is an attachment.
+fn push_value(dest: &mut String, item: &Item) -> Result<()> {
+    let Some(value) = item.value().as_text() else {
+        return Err(Error::UnexpectedAttachment(item.name().to_owned()));
+    };
+
+    dest.push_str(value);
+    dest.push('\n');
+
+    Ok(())
+}
+
+/// Asks the user to choose one of the records `candidates` matching `pat`, if
+/// both standard input and standard error are terminals. An empty line or EOF
+/// cancels the choice.
+///
+/// Returns the index of the chosen record. See [`RecordPath::find_choosing`].
+pub fn choose_record(pat: &str, candidates: &[RecordPath]) -> Option<usize> {
+    use io::IsTerminal;
+
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return None;
+    }
+
+    info!("'{pat}' matches several records:");
+
+    for (i, path) in candidates.iter().enumerate() {
+        eprintln!("{:>4}  {path}", i + 1);
+    }
+
+    loop {
+        let line = input!("Choose a record [1-{}]: ", candidates.len()).ok()?;
+
+        match line.trim() {
+            "" => return None,
+
+            l => match l.parse::<usize>() {
+                Ok(n) if (1..=candidates.len()).contains(&n) =>
+                    return Some(n - 1),
+                _ => continue
+            }
+        }
+    }
+}
+
+/// Reports on standard error that the target `pat` resolved to `path`, unless
+/// they are identical.
+fn report_resolved(pat: &RecordPath, path: &RecordPath) {
+    if pat != path {
+        info!("'{pat}' resolved to '{path}'");
+    }
+}
+
+/// Prints the root group of `data` as [`PrintTarget::print_lists`] does.
+pub fn print_root_list(data: &Node<Record>, format: Format) -> Result<()> {
+    print_root(data, Layout::List, format)
+}
+
+/// Prints the root group of `data` as [`PrintTarget::print_trees`] does.
+pub fn print_root_tree(data: &Node<Record>, format: Format) -> Result<()> {
+    print_root(data, Layout::Tree, format)
+}
+
+fn print_root(
+    data: &Node<Record>,
+    layout: Layout,
+    format: Format
+) -> Result<()> {
+    if format == Format::Json {
+        let path = RecordPath::of(&data.borrow());
+
+        return write_records_json(&[(path, Rc::clone(data))], layout)
+            .map_err(Error::WritingStdout);
+    }
+
+    match layout {
+        Layout::List => println!("{}", Record::display_list(data)),
+        Layout::Tree => println!("{}", Record::display_tree(data))
+    }
+
+    Ok(())
+}
+
+/// Writes a JSON document to standard output with `op`.
+fn write_json<O>(op: O) -> Result<()>
     where
-        F: Fn(RecordPath) -> Result<D>,
-        D: Display
+        O: FnOnce(&mut JsonWriter<io::StdoutLock>) -> json::Result<()>
 {
-    let mut paths = paths.into_iter();
+    let mut writer = JsonWriter::new(io::stdout().lock());
 
-    if let Some(p) = paths.next() {
-        match f(p) {
-            Ok(d) => println!("{d}"),
-            Err(e) => e.print_full()
-        }
+    op(&mut writer)
+        .and_then(|()| writer.finish())
+        .map_err(Error::WritingStdout)
+}
 
-        for p in paths {
-            println!();
+/// Writes an array of the paths, kinds and values of `items` as JSON to
+/// standard output.
+///
+/// Values are written straight from the records. Attachments are encoded in
+/// Base64 if `attachments` is true, and are otherwise skipped with an error.
+fn write_values_json(
+    items: &[(RecordPath, Node<Item>)],
+    attachments: bool
+) -> io::Result<()> {
+    let mut w = JsonWriter::new(io::stdout().lock());
+
+    w.begin_array()?;
+
+    for (path, item) in items {
+        let item = item.borrow();
+        let encoded;
+
+        let (kind, value) = match item.value() {
+            Value::Text(t) => ("item", t.as_str()),
+
+            Value::Binary(b) if attachments => {
+                encoded = base64_of(b);
+                ("attachment", encoded.as_str())
+            }
+
+            Value::Binary(_) => {
+                let name = item.name().to_owned();
+                Error::UnexpectedAttachment(name).print_full();
+                continue;
+            }
+        };
+
+        w.begin_object()?;
+        w.member("path", &path.to_string())?;
+        w.member("kind", kind)?;
+        w.member("value", value)?;
+        w.end_object()?;
+    }
+
+    w.end_array()?;
+    w.finish()
+}
+
+/// Writes an array of `recs` with their full paths as JSON to standard output.
+/// See [`write_record_json`].
+fn write_records_json(
+    recs: &[(RecordPath, Node<Record>)],
+    layout: Layout
+) -> io::Result<()> {
+    let mut w = JsonWriter::new(io::stdout().lock());
 
-            match f(p) {
-                Ok(d) => println!("{d}"),
-                Err(e) => e.print_full()
+    w.begin_array()?;
+
+    for (path, rec) in recs {
+        write_record_json(&mut w, rec, path, layout)?;
+    }
+
+    w.end_array()?;
+    w.finish()
+}
+
+/// Writes `rec` as a JSON object with its name, full path `path` and kind.
+///
+/// The members of groups are included as objects of their own, with members of
+/// their own if `layout` is [`Layout::Tree`]. Values are never included.
+fn write_record_json<W: io::Write>(
+    w: &mut JsonWriter<W>,
+    rec: &Node<Record>,
+    path: &RecordPath,
+    layout: Layout
+) -> json::Result<()> {
+    let rec = rec.borrow();
+
+    w.begin_object()?;
+    w.member("name", &rec.do_with_meta(|m| m.name().to_owned()))?;
+    w.member("path", &path.to_string())?;
+    w.member("kind", kind_of(&rec))?;
+
+    if let Record::Group(g) = &*rec {
+        w.key("members")?;
+        w.begin_array()?;
+
+        for (name, member) in g.borrow().members() {
+            let mut member_path = path.clone();
+            member_path.push(name);
+
+            match (layout, &*member.borrow()) {
+                (Layout::Tree, _) | (_, Record::Item(_)) =>
+                    write_record_json(w, member, &member_path, layout)?,
+
+                // Members of nested groups are omitted in lists.
+                (Layout::List, m) => {
+                    w.begin_object()?;
+                    w.member("name", name)?;
+                    w.member("path", &member_path.to_string())?;
+                    w.member("kind", kind_of(m))?;
+                    w.end_object()?;
+                }
             }
         }
+
+        w.end_array()?;
+    }
+
+    w.end_object()
+}
+
+/// Returns the kind of `rec` as written in JSON.
+fn kind_of(rec: &Record) -> &'static str {
+    match rec {
+        Record::Group(_) => "group",
+        Record::Item(i) => match i.borrow().value() {
+            Value::Text(_) => "item",
+            Value::Binary(_) => "attachment"
+        }
     }
 }
+
+/// Returns `data` encoded in standard Base64.
+fn base64_of(data: &[u8]) -> Secret<String> {
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+
+    // The output is allocated once, so it is never copied.
+    let len = base64::encoded_len(data.len(), true).unwrap_or(0);
+    let mut result = Secret::new(String::with_capacity(len));
+
+    STANDARD.encode_string(data, &mut result);
+    result
+}
@@ -14,11 +14,16 @@ use std::{
 
 use std::fmt::Display;
 
+use std::collections::BTreeSet;
+
 pub enum Error {
     NonUtf8Data(str::Utf8Error),
     Deserialisation(record::Error),
     Serialisation(record::Error),
-    InvalidRecord(find::Error)
+    InvalidRecord(find::Error),
+    /// A record name which would make a record unreachable, and the group
+    /// containing it.
+    InvalidName(record::Error, String, String)
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -70,8 +75,11 @@ pub fn bytes_from(rec_secret: Secret<Node<Record>>) -> Result<Vec<u8>> {
 pub fn validate(s: &str) -> Result<()> {
     use find::Error::NotAGroup;
 
-    let ir = Ir::from_str(s)?;
-    let rec = Secret::new(Record::from(ir));
+    let ir = Secret::new(Ir::from_str(s)?);
+
+    check_names(&ir)?;
+
+    let rec = Secret::new(Record::from(ir.into_inner()));
     let rec_ref = &*rec.borrow();
 
     match rec_ref {
@@ -102,7 +110,41 @@ impl Display for Error {
             NonUtf8Data(e)     => write!(f, "{e}"),
             Deserialisation(e) => write!(f, "{e}"),
             Serialisation(e)   => write!(f, "{e}"),
-            InvalidRecord(e)   => write!(f, "{e}")
+            InvalidRecord(e)   => write!(f, "{e}"),
+            InvalidName(e, name, group) =>
+                write!(f, "'{name}' in group '{group}': {e}")
         }
     }
 }
+
+/// Verifies that every record within `ir` can be reached by a record path.
+///
+/// This is the case if the names of the members of each group are valid and
+/// unique within that group. Duplicate names must never reach
+/// [`Record::from`], as only one of the records could be kept.
+fn check_names(ir: &Ir) -> Result<()> {
+    use record::Error::{InvalidName, AlreadyExists};
+
+    let Ir::Group { name: group, members, .. } = ir else {
+        return Ok(());
+    };
+
+    let mut names = BTreeSet::new();
+
+    for m in members {
+        let name = m.name();
+        let invalid = |e| Error::InvalidName(e, name.to_owned(), group.clone());
+
+        if !record::is_valid_name(name) {
+            return Err(invalid(InvalidName));
+        }
+
+        if !names.insert(name) {
+            return Err(invalid(AlreadyExists));
+        }
+
+        check_names(m)?;
+    }
+
+    Ok(())
+}
@@ -2,11 +2,20 @@ use Cmd::{Read, Edit, Meta};
 
 use crate::find::{MatchKind, RecordPath};
 
+use crate::gen::Classes;
+
+use crate::util::clip::Selection;
+
 use std::{num, fmt};
 
+use std::num::NonZeroUsize;
+
 use std::fmt::Display;
 
-use std::time::Duration;
+use std::{
+    time::Duration,
+    path::PathBuf
+};
 
 /// The command to be executed.
 pub enum Cmd {
@@ -21,6 +30,10 @@ pub enum ReadCmd {
     Clip(RecordPath),
     List(Option<Vec<RecordPath>>),
     Tree(Option<Vec<RecordPath>>),
+    /// XXX: also matches item values if `values`
+    Find { pat: String, values: bool },
+    /// XXX: writes to a new file with mode 0600
+    Extract { path: RecordPath, file: PathBuf },
     Export
 }
 
@@ -38,6 +51,15 @@ pub enum EditCmd {
                                                     // accept whitespace escapes
                                                     // (multiline values) for input
     ChangeValue { paths: Vec<RecordPath> },
+    /// XXX: creates an attachment item with the contents of `file`
+    Attach { path: SplitPath, file: PathBuf },
+    /// XXX: creates the item or replaces its value with a random password, or
+    /// passphrase if `phrase`
+    Generate { path: SplitPath, phrase: bool },
+    // Group operations.
+    /// XXX: shows the group's password policy if `rules` is empty, and
+    /// otherwise sets each rule (key and value), removing those without value
+    Policy { path: RecordPath, rules: Vec<(String, String)> },
 }
 
 /// TUI management and information.
@@ -63,7 +85,21 @@ pub struct SplitPath {
 #[derive(Clone, Copy)]
 pub enum OptVal {
     ClipTime(Duration),
-    MatchKind(MatchKind)
+    ClipSelection(Selection),
+    ClipRestore(bool),
+    ClipPastes(Option<NonZeroUsize>),
+    ClipTerminal(bool),
+    TimedShow(bool),
+    RawShow(bool),
+    MatchKind(MatchKind),
+    ReportPath(bool),
+    GenLength(usize),
+    GenClasses(Classes),
+    GenAmbiguous(bool),
+    PhraseWords(NonZeroUsize),
+    PhraseSep(char),
+    PhraseCaps(bool),
+    PhraseDigit(bool)
 }
 
 /// Non-algebraic [`Cmd`] for parsing and validation.
@@ -73,6 +109,8 @@ pub enum CmdVerb {
     Clip,
     List,
     Tree,
+    Find,
+    Extract,
     Export,
 
     Remove,
@@ -81,6 +119,10 @@ pub enum CmdVerb {
     CreateItem,
     CreateGroup,
     ChangeValue,
+    Attach,
+    Generate,
+    GeneratePhrase,
+    Policy,
 
     SetOption,
     ShowConfig,
@@ -187,6 +229,28 @@ impl Cmd {
                 src: next_into(&mut args),
                 dest: next_into(&mut args)
             }),
+            Find => Read(find_from(args)?),
+            Extract => Read(ReadCmd::Extract {
+                path: next_into(&mut args),
+                file: next_into(&mut args)
+            }),
+            Attach => Edit(EditCmd::Attach {
+                // As with `CreateItem`.
+                path: split(next_into(&mut args))?,
+                file: next_into(&mut args)
+            }),
+            Generate | GeneratePhrase => Edit(EditCmd::Generate {
+                // As with `CreateItem`.
+                path: split(next_into(args))?,
+                phrase: matches!(verb, GeneratePhrase)
+            }),
+            Policy => Edit(EditCmd::Policy {
+                path: next_into(&mut args),
+                rules: args.map(|a| match a.split_once('=') {
+                    Some((k, v)) => Ok((k.to_owned(), v.to_owned())),
+                    None => Err(Error::InvalidArg(a))
+                }).collect::<Result<_>>()?
+            }),
             SetOption => Meta(MetaCmd::SetOpt(OptVal::new(
                 next_into(&mut args),
                 next_into(&mut args),
@@ -203,11 +267,56 @@ impl OptVal {
                     .map_err(|e| Error::InvalidIntArg(val, e))?
             )),
 
+             "cs" | "clip-select" => Self::ClipSelection(
+                Selection::from_str(&val)
+                    .ok_or(Error::InvalidArg(val))?
+            ),
+
+             "cr" | "clip-restore" => Self::ClipRestore(on_off(val)?),
+
+             // 0 for unlimited pastes.
+             "cn" | "clip-pastes" => Self::ClipPastes(NonZeroUsize::new(
+                val.parse::<usize>()
+                    .map_err(|e| Error::InvalidIntArg(val, e))?
+            )),
+
+             "co" | "clip-osc52" => Self::ClipTerminal(on_off(val)?),
+
+             "st" | "show-timed" => Self::TimedShow(on_off(val)?),
+             "sr" | "show-raw" => Self::RawShow(on_off(val)?),
+
              "mk" | "match-kind" => Self::MatchKind(
                 MatchKind::from_str(&val)
                     .ok_or(Error::InvalidArg(val))?
             ),
 
+             "rp" | "report-path" => Self::ReportPath(on_off(val)?),
+
+             "gl" | "gen-length" => Self::GenLength(
+                val.parse::<usize>()
+                    .map_err(|e| Error::InvalidIntArg(val, e))?
+            ),
+
+             "gc" | "gen-classes" => Self::GenClasses(
+                Classes::from_str(&val)
+                    .ok_or(Error::InvalidArg(val))?
+            ),
+
+             "ga" | "gen-ambiguous" => Self::GenAmbiguous(on_off(val)?),
+
+             "pw" | "phrase-words" => Self::PhraseWords(
+                val.parse::<NonZeroUsize>()
+                    .map_err(|e| Error::InvalidIntArg(val, e))?
+            ),
+
+             "ps" | "phrase-sep" => Self::PhraseSep(
+                val.parse::<char>()
+                    .map_err(|_| Error::InvalidArg(val))?
+            ),
+
+             "pc" | "phrase-caps" => Self::PhraseCaps(on_off(val)?),
+             "pd" | "phrase-digit" => Self::PhraseDigit(on_off(val)?),
+
             _ => return Err(Error::InvalidArg(name))
         })
     }
@@ -222,6 +331,8 @@ impl CmdVerb {
             "cl" | "clip" => Clip,
             "ls" | "list" => List,
             "tr" | "tree" => Tree,
+            "fd" | "find" => Find,
+            "xt" | "extract" => Extract,
             "ex" | "export" => Export,
 
             "rm" | "remove" => Remove,
@@ -230,6 +341,10 @@ impl CmdVerb {
             "mg" | "mkgrp" => CreateGroup,
             "mi" | "mkitm" => CreateItem,
             "cv" | "chval" => ChangeValue,
+            "ah" | "attach" => Attach,
+            "gn" | "gen" => Generate,
+            "gp" | "genphrase" => GeneratePhrase,
+            "po" | "policy" => Policy,
 
             "so" | "setopt" => SetOption,
             "sc" | "showconf" => ShowConfig,
@@ -254,19 +369,26 @@ impl CmdVerb {
             Export | Exit | Abort | ShowConfig =>
                 if a.is_empty() { Ok(a) } else { Err(ExtraArg(take(a, 0))) }
 
-            Clip => match a.len() {
+            Clip | Generate | GeneratePhrase => match a.len() {
                 1 => Ok(a),
                 0 => Err(MissingArg),
                 _ => Err(Error::ExtraArg(take(a, 1)))
             }
 
-            Move | Copy | SetOption => match a.len() {
+            // The pattern may be accompanied by a flag.
+            Find => match a.len() {
+                1 | 2 => Ok(a),
+                0 => Err(MissingArg),
+                _ => Err(ExtraArg(take(a, 2)))
+            }
+
+            Move | Copy | Extract | Attach | SetOption => match a.len() {
                 2 => Ok(a),
                 1 | 0 => Err(MissingArg),
                 _ => Err(ExtraArg(take(a, 2)))
             }
 
-            Show | CreateGroup | CreateItem | ChangeValue | Remove =>
+            Show | CreateGroup | CreateItem | ChangeValue | Remove | Policy =>
                 if !a.is_empty() { Ok(a) } else { Err(MissingArg) }
 
             List | Tree | ShowUsage => Ok(a)
@@ -274,22 +396,53 @@ impl CmdVerb {
     }
 }
 
+/// Returns a [`ReadCmd::Find`] from a pattern and an optional flag in any
+/// order.
+fn find_from(args: impl Iterator<Item = String>) -> Result<ReadCmd> {
+    let mut pat = Option::<String>::None;
+    let mut values = false;
+
+    for a in args {
+        match a.as_str() {
+            "-V" | "--values" => values = true,
+            _ if pat.is_none() => pat = Some(a),
+            _ => return Err(Error::ExtraArg(a))
+        }
+    }
+
+    Ok(ReadCmd::Find {
+        pat: pat.ok_or(Error::MissingArg)?,
+        values
+    })
+}
+
+/// Parses the value of a boolean option.
+fn on_off(val: String) -> Result<bool> {
+    match val.as_str() {
+        "on"  => Ok(true),
+        "off" => Ok(false),
+         _    => Err(Error::InvalidArg(val))
+    }
+}
+
 fn split_each<I>(paths: I) -> Result<Vec<SplitPath>>
     where
         I: Iterator<Item = RecordPath>
 {
-    paths.map(|path| {
-        // If the path only contains one element, root will be taken as the
-        // leading path.
-        let (leading, trailing) = path
-            .split_last()
-            .map_err(Error::InvalidName)?;
-
-        Ok(SplitPath {
-            group: leading,
-            name: trailing.into_inner()
-        })
-    }).collect()
+    paths.map(split).collect()
+}
+
+fn split(path: RecordPath) -> Result<SplitPath> {
+    // If the path only contains one element, root will be taken as the leading
+    // path.
+    let (leading, trailing) = path
+        .split_last()
+        .map_err(Error::InvalidName)?;
+
+    Ok(SplitPath {
+        group: leading,
+        name: trailing
+    })
 }
 
 fn into_collect<I, J>(iter: impl Iterator<Item = I>) -> Vec<J>
@@ -1,30 +1,33 @@
 mod cmd;
 
-use Status::{Running, Stopped, Aborted, Clipped};
+use Status::{Running, Stopped, Aborted};
 
 use cmd::{Cmd, ReadCmd, EditCmd, MetaCmd, SplitPath, OptVal};
 
-use crate::{input, err, info};
+use crate::{input, confirm, err, warn, info};
 
-use crate::{error, output};
+use crate::{config, error, output, gen};
 
-use crate::find::MatchKind;
+use crate::find::{MatchKind, RecordPath};
 
-use crate::util::{user_io, record};
+use crate::output::choose_record;
+
+use crate::util::{user_io, record, file, clip};
 
 use crate::util::secret::Erase;
 
 use crate::util::{
-    record::{Record, Group, Node, Ir},
-    secret::Secret,
-    proc::Process
+    record::{Record, Group, Value, Node, Ir},
+    secret::Secret
 };
 
 use std::{io, mem, fmt};
 
-use std::fmt::Display;
+use std::rc::Rc;
 
-use std::time::Duration;
+use std::process::Child;
+
+use std::fmt::Display;
 
 // TODO: perhaps add option for hiding input
 pub struct Tui {
@@ -32,12 +35,27 @@ pub struct Tui {
     /// Useful to avoid unnecessarily writing to a file if the data is
     /// unchanged.
     changes_made: bool,
-    status: Status
+    status: Status,
+    /// The clipboard holder processes started by the `clip` command, which
+    /// are reaped once they exit.
+    holders: Vec<Child>
 }
 
 pub struct Config {
     pub match_kind: MatchKind,
-    pub clip_time: Duration
+    /// How to hold items copied to the clipboard.
+    pub clip: clip::HoldOpts,
+    /// Whether to erase items displayed with the `show` command once the
+    /// clipboard duration runs out.
+    pub timed_show: bool,
+    /// Whether to display items outside a terminal without restrictions.
+    pub raw_show: bool,
+    /// Whether to report the full paths targets resolve to.
+    pub report_path: bool,
+    /// How to generate passwords with the `gen` command.
+    pub gen_policy: gen::Policy,
+    /// How to generate passphrases with the `genphrase` command.
+    pub passphrase: gen::Passphrase
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -49,10 +67,7 @@ pub enum Status {
     /// Exited the TUI abnormally.
     ///
     /// Signals that the pass file should not be written to.
-    Aborted,
-    /// Used in the clipboard holder process, to signal that the pass file
-    /// should not be written to (thus avoiding a race condition).
-    Clipped
+    Aborted
 }
 
 pub type Error = error::Error;
@@ -81,7 +96,8 @@ impl Tui {
         Self {
             conf,
             changes_made: false,
-            status: Stopped
+            status: Stopped,
+            holders: Vec::new()
         }
     }
 
@@ -92,6 +108,8 @@ impl Tui {
         self.status = Running;
 
         while self.status == Running {
+            self.reap_holders();
+
             match input!() {
                 Ok(l) => {
                     let cmd = match Cmd::from_str(&l) {
@@ -110,16 +128,21 @@ impl Tui {
                     self.status = Stopped;
                 } else {
                     self.status = Aborted;
+                    self.reap_holders();
                     return Err(Error::ReadingInput(e))
                 }
             }
         }
 
+        self.reap_holders();
+
         Ok(())
     }
 
-    pub fn status(&self) -> Status {
-        self.status
+    /// Reaps the holder processes which exited, so that they don't linger as
+    /// zombies. Those still running are kept.
+    fn reap_holders(&mut self) {
+        self.holders.retain_mut(|h| !matches!(h.try_wait(), Ok(Some(_))));
     }
 
     pub fn should_save_data(&self) -> bool {
@@ -132,8 +155,22 @@ impl Config {
         use OptVal::*;
 
         match opt {
-            ClipTime(t) => self.clip_time = t,
-            MatchKind(k) => self.match_kind = k
+            ClipTime(t) => self.clip.time = t,
+            ClipSelection(s) => self.clip.selection = s,
+            ClipRestore(r) => self.clip.restore = r,
+            ClipPastes(n) => self.clip.pastes = n,
+            ClipTerminal(t) => self.clip.terminal = t,
+            TimedShow(t) => self.timed_show = t,
+            RawShow(r) => self.raw_show = r,
+            MatchKind(k) => self.match_kind = k,
+            ReportPath(r) => self.report_path = r,
+            GenLength(l) => self.gen_policy.length = l,
+            GenClasses(c) => self.gen_policy.classes = c,
+            GenAmbiguous(a) => self.gen_policy.ambiguous = a,
+            PhraseWords(w) => self.passphrase.words = w,
+            PhraseSep(s) => self.passphrase.separator = s,
+            PhraseCaps(c) => self.passphrase.capitalise = c,
+            PhraseDigit(d) => self.passphrase.digit = d
         }
     }
 }
@@ -145,8 +182,31 @@ impl Display for Config {
         let name = <str as user_io::Style>::as_name;
 
         // Writes each element aligned and coloured.
-        write!(f, "{} {}\n", name("match-kind :"), self.match_kind)?;
-        write!(f, "{} {}", name("clip-time  :"), self.clip_time.as_secs())
+        let gen::Policy { length, classes, ambiguous } = self.gen_policy;
+        let gen::Passphrase {
+            words, separator, capitalise, digit
+        } = self.passphrase;
+        let pastes = self.clip.pastes
+            .map_or_else(|| String::from("unlimited"), |n| n.to_string());
+        let restore = on_off(self.clip.restore);
+        let terminal = on_off(self.clip.terminal);
+
+        write!(f, "{} {}\n", name("match-kind   :"), self.match_kind)?;
+        write!(f, "{} {}\n", name("clip-time    :"), self.clip.time.as_secs())?;
+        write!(f, "{} {}\n", name("clip-select  :"), self.clip.selection)?;
+        write!(f, "{} {}\n", name("clip-restore :"), restore)?;
+        write!(f, "{} {}\n", name("clip-pastes  :"), pastes)?;
+        write!(f, "{} {}\n", name("clip-osc52   :"), terminal)?;
+        write!(f, "{} {}\n", name("show-timed   :"), on_off(self.timed_show))?;
+        write!(f, "{} {}\n", name("show-raw     :"), on_off(self.raw_show))?;
+        write!(f, "{} {}\n", name("report-path  :"), on_off(self.report_path))?;
+        write!(f, "{} {}\n", name("gen-length   :"), length)?;
+        write!(f, "{} {}\n", name("gen-classes  :"), classes)?;
+        write!(f, "{} {}\n", name("gen-ambiguous:"), on_off(ambiguous))?;
+        write!(f, "{} {}\n", name("phrase-words :"), words)?;
+        write!(f, "{} '{}'\n", name("phrase-sep   :"), separator)?;
+        write!(f, "{} {}\n", name("phrase-caps  :"), on_off(capitalise))?;
+        write!(f, "{} {}", name("phrase-digit :"), on_off(digit))
     }
 }
 
@@ -165,40 +225,55 @@ impl Cmd {
 impl ReadCmd {
     fn exec(self, data: &Node<Record>, tui: &mut Tui) -> Result {
         use ReadCmd::*;
-        use output::{PrintTarget, ClipTarget};
+        use output::{PrintTarget, ClipTarget, SearchTarget, ExtractTarget};
+        use output::Format;
 
-        let Config { match_kind, clip_time } = tui.conf;
+        let Config {
+            match_kind, clip, timed_show, raw_show, report_path, ..
+        } = tui.conf;
 
         match self {
-            Show(paths) => PrintTarget::new(paths, match_kind)
-                .print_values(data),
+            Show(paths) => {
+                output::check_show_output(raw_show)?;
 
-            Clip(path) => {
-                let result_forked = ClipTarget::new(path, match_kind, clip_time)
-                    .clip(data);
-
-                let (opt_proc, result) = result_forked;
+                PrintTarget::new(paths, match_kind, report_path, Format::Text)
+                    .print_values(data, false, timed_show.then_some(clip.time));
+            }
 
-                //// The clipboard should exit immediately without performing IO.
-                if let Some(Process::Child) = opt_proc {
-                    tui.status = Clipped;
-                }
+            // The holder process runs on its own, and is reaped later.
+            Clip(path) => {
+                let holder = ClipTarget::new(
+                    path, match_kind, Vec::new(), clip, report_path
+                ).clip(data)?;
 
-                return result;
+                tui.holders.push(holder);
             }
 
             List(opt_paths) => match opt_paths {
-                Some(paths) => PrintTarget::new(paths, match_kind)
-                    .print_lists(data),
+                Some(paths) => PrintTarget::new(
+                    paths, match_kind, report_path, Format::Text
+                ).print_lists(data),
                 None => println!("{}", Record::display_list(data))
             }
 
             Tree(opt_paths) => match opt_paths {
-                Some(paths) => PrintTarget::new(paths, match_kind)
-                    .print_trees(data),
+                Some(paths) => PrintTarget::new(
+                    paths, match_kind, report_path, Format::Text
+                ).print_trees(data),
                 None => println!("{}", Record::display_tree(data))
             }
 
+            Find { pat, values } =>
+                SearchTarget::new(pat, match_kind, values, Format::Text)
+                    .print_matches(data)?,
+
+            Extract { path, file } => {
+                info!("Extracting '{path}' to '{}'", file.display());
+
+                ExtractTarget::new(path, match_kind, file)
+                    .extract(data)?;
+            }
+
             Export => {
                 let ir = Secret::new(Ir::clone_from(data));
                 println!("{}", *ir);
@@ -218,26 +293,50 @@ impl EditCmd {
 
         match self {
             Remove { paths } => for p in paths {
-                let mut rec = unwrap_continue!(p.find_in(data, match_kind));
+                let mut recs = unwrap_continue!(p.find_choosing(
+                    data, |p, r| p.find_all_in(r, match_kind), choose_record
+                ));
 
-                let parent = match rec.borrow().parent() {
-                    Some(p) => p,
-                    None => err_continue!("'{p}': cannot remove root group")
-                };
+                let root_idx = recs.iter()
+                    .position(|r| r.borrow().parent().is_none());
+
+                // The root group can only be matched directly or by '**'.
+                if let Some(idx) = root_idx {
+                    err!("'{p}': cannot remove root group");
+                    recs.remove(idx);
+                }
+
+                let recs = outermost(recs);
 
-                let mut parent = parent.borrow_mut();
+                // Glob patterns may match more records than the user expects.
+                if p.is_glob() && !recs.is_empty() {
+                    for r in &recs {
+                        println!("{}", RecordPath::of(&r.borrow()));
+                    }
 
-                // We must clone the name to avoid calling `rec.do_with_meta()`.
-                // If we did so, `parent.remove()` would panic as it mutably
-                // borrows `rec`.
-                let name = rec.borrow()
-                    .do_with_meta(|meta| meta.name().to_owned());
+                    if !confirm!("Remove {} records?", recs.len())? {
+                        continue;
+                    }
+                }
 
-                info!("Removing '{name}' in '{}'", parent.name());
-                // `rec` is known to be a child of `parent`, so it can be
-                // infallibly removed.
-                parent.remove(&name).unwrap();
-                rec.erase();    // `rec` is now orphaned and should be erased.
+                for mut rec in recs {
+                    // Only the root group has no parent, and it was excluded.
+                    let parent = rec.borrow().parent().unwrap();
+                    let mut parent = parent.borrow_mut();
+
+                    // We must clone the name to avoid calling
+                    // `rec.do_with_meta()`. If we did so, `parent.remove()`
+                    // would panic as it mutably borrows `rec`.
+                    let name = rec.borrow()
+                        .do_with_meta(|meta| meta.name().to_owned());
+
+                    info!("Removing '{name}' in '{}'", parent.name());
+                    // `rec` is known to be a child of `parent`, so it can be
+                    // infallibly removed.
+                    parent.remove(&name).unwrap();
+                    // `rec` is now orphaned and should be erased.
+                    rec.erase();
+                }
             }
 
             Move { src, dest } => {
@@ -252,9 +351,9 @@ impl EditCmd {
             }
 
             CreateItem { paths } => for SplitPath { group, name } in paths {
-                let parent = unwrap_continue!(
-                    group.find_group_in(data, match_kind)
-                );
+                let parent = unwrap_continue!(group.find_choosing(
+                    data, |p, r| p.find_group_in(r, match_kind), choose_record
+                ));
 
                 info!("Creating item '{name}' in '{}'", parent.borrow().name());
 
@@ -267,25 +366,34 @@ impl EditCmd {
                 }
 
                 let value = unwrap_continue!(input_escaped("Value: "));
-                let item = Record::new_item(name, value);
+                check_policy(&name, &value, &parent);
+
+                let item = Record::new_item(name, Value::Text(value));
 
                 unwrap_continue!(insert(item, &parent));
             }
 
             CreateGroup { paths } => for SplitPath { group, name } in paths {
-                let parent = unwrap_continue!(
-                    group.find_group_in(data, match_kind)
-                );
+                let parent = unwrap_continue!(group.find_choosing(
+                    data, |p, r| p.find_group_in(r, match_kind), choose_record
+                ));
 
                 info!("Creating group '{name}' in '{}'", parent.borrow().name());
                 unwrap_continue!(insert(Record::new_group(name), &parent));
             }
 
             ChangeValue { paths } => for p in paths {
-                let item = unwrap_continue!(p.find_item_in(data, match_kind));
+                let item = unwrap_continue!(p.find_choosing(
+                    data, |p, r| p.find_item_in(r, match_kind), choose_record
+                ));
                 // An item cannot be root, so `item` must have a parent.
                 let parent = item.borrow().parent().unwrap();
 
+                if let Value::Binary(_) = item.borrow().value() {
+                    let name = item.borrow().name().to_owned();
+                    err_continue!("{}", Error::ReplacingAttachment(name));
+                }
+
                 info!(
                     "Changing value of '{}' in '{}'",
                     item.borrow().name(),
@@ -294,11 +402,138 @@ impl EditCmd {
 
                 // We don't need to wrap this in a `Secret` because it will be
                 // immediately and infallibly swapped into a protected record.
-                let mut value = unwrap_continue!(input_escaped("New value: "));
+                let mut value = Value::Text(
+                    unwrap_continue!(input_escaped("New value: "))
+                );
+
+                if let Value::Text(text) = &value {
+                    check_policy(item.borrow().name(), text, &parent);
+                }
 
                 mem::swap(item.borrow_mut().value_mut(), &mut value);
                 value.erase();      // Erase the old value.
             }
+
+            Attach { path: SplitPath { group, name }, file } => {
+                let parent = group.find_choosing(
+                    data, |p, r| p.find_group_in(r, match_kind), choose_record
+                )?;
+
+                info!(
+                    "Attaching '{}' as '{name}' in '{}'",
+                    file.display(),
+                    parent.borrow().name()
+                );
+
+                let data = file::read_all(&file)
+                    .map_err(|e| Error::ReadingAttachment(e, file))?;
+
+                insert(Record::new_item(name, Value::Binary(data)), &parent)?;
+            }
+
+            Generate { path: SplitPath { group, name }, phrase } => {
+                let parent = group.find_choosing(
+                    data, |p, r| p.find_group_in(r, match_kind), choose_record
+                )?;
+
+                let item = match parent.borrow().get(&name) {
+                    Ok(rec) => match &*rec.borrow() {
+                        Record::Item(item) => Some(item.clone()),
+                        Record::Group(_) => return Err(Error::AddingRecord(
+                            AlreadyExists, name,
+                            clone_name(&parent)
+                        ))
+                    }
+                    Err(_) => None
+                };
+
+                if let Some(item) = &item {
+                    if let Value::Binary(_) = item.borrow().value() {
+                        return Err(Error::ReplacingAttachment(name));
+                    }
+                }
+
+                let policy = tui.conf.gen_policy;
+
+                let pw = if phrase {
+                    let phrase = tui.conf.passphrase;
+
+                    info!(
+                        "Estimated entropy: about {:.0} bits",
+                        phrase.entropy()
+                    );
+                    phrase.generate()
+                } else {
+                    match gen::Rules::nearest(&parent)? {
+                        Some((rules, _)) => rules.generate(&policy)?,
+                        None => policy.generate()?
+                    }
+                };
+
+                // Passphrases cannot be adjusted to a policy.
+                check_policy(&name, &pw, &parent);
+
+                match item {
+                    Some(item) => {
+                        info!(
+                            "Generating new value of '{name}' in '{}'",
+                            parent.borrow().name()
+                        );
+
+                        // As with `ChangeValue`.
+                        let mut value = Value::Text(pw.into_inner());
+
+                        mem::swap(item.borrow_mut().value_mut(), &mut value);
+                        value.erase();      // Erase the old value.
+                    }
+
+                    None => {
+                        info!(
+                            "Generating item '{name}' in '{}'",
+                            parent.borrow().name()
+                        );
+
+                        let item = Record::new_item(
+                            name, Value::Text(pw.into_inner())
+                        );
+                        insert(item, &parent)?;
+                    }
+                }
+            }
+
+            Policy { path, rules } => {
+                let group = path.find_choosing(
+                    data, |p, r| p.find_group_in(r, match_kind), choose_record
+                )?;
+
+                if rules.is_empty() {
+                    print_policy(&group.borrow());
+                    return Ok(());
+                }
+
+                let mut applied = false;
+
+                for (key, val) in rules {
+                    let mut group = group.borrow_mut();
+                    let name = group.name();
+
+                    if val.is_empty() {
+                        info!("Removing policy rule '{key}' of '{name}'");
+                    } else {
+                        info!("Setting policy rule '{key}' of '{name}'");
+                    }
+
+                    unwrap_continue!(
+                        gen::Rules::set_in(group.attrs_mut(), &key, &val)
+                    );
+                    applied = true;
+                }
+
+                // Nothing changed if every rule was invalid.
+                if !applied {
+                    return Ok(());
+                }
+            }
         }
 
         tui.changes_made = true;
@@ -316,7 +551,7 @@ impl MetaCmd {
             ShowConfig => println!("{}", tui.conf),
 
             // TODO
-            ShowUsage(verb) => {
+            ShowUsage(_verb) => {
                 err!("unimplemented");
 
                 println!("\
@@ -324,6 +559,8 @@ sh | show => Show,
 cl | clip => Clip,
 ls | list => List,
 tr | tree => Tree,
+fd | find => Find,
+xt | extract => Extract,
 ex | export => Export,
 
 rm | remove => Remove,
@@ -332,6 +569,10 @@ cp | copy => Copy,
 mg | mkgrp => CreateGroup,
 mi | mkitm => CreateItem,
 cv | chval => ChangeValue,
+ah | attach => Attach,
+gn | gen => Generate,
+gp | genphrase => GeneratePhrase,
+po | policy => Policy,
 
 so | setopt => SetOption,
 sc | showconf => ShowConfig,
@@ -387,6 +628,52 @@ fn unescape(s: &str) -> String {
     result
 }
 
+/// Warns if `value` is a password breaking the policy applying to `parent`,
+/// in which it is named `name`.
+fn check_policy(name: &str, value: &str, parent: &Node<Group>) {
+    if name != config::DEFAULT_ITEM {
+        return;
+    }
+
+    match gen::Rules::nearest(parent) {
+        Ok(Some((rules, group))) => {
+            let reasons = rules.check(value).iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+
+            if !reasons.is_empty() {
+                warn!(
+                    "'{name}' breaks the password policy of '{group}': {}",
+                    reasons.join(", ")
+                );
+            }
+        }
+
+        Ok(None) => (),
+        Err(e) => warn!("{e}")
+    }
+}
+
+/// Displays each rule of the password policy of `group`.
+fn print_policy(group: &Group) {
+    let rules = group.attrs().iter()
+        .filter_map(|(k, v)| Some((k.strip_prefix(gen::rules::KEY_PREFIX)?, v)))
+        .collect::<Vec<_>>();
+
+    if rules.is_empty() {
+        info!("'{}' has no password policy of its own", group.name());
+    }
+
+    for (key, val) in rules {
+        println!("{key}={val}");
+    }
+}
+
+/// Displays `b` as the value of a boolean option.
+fn on_off(b: bool) -> &'static str {
+    if b { "on" } else { "off" }
+}
+
 /// erase `rec` on failure
 fn insert(mut rec: Node<Record>, group: &Node<Group>) -> Result {
     Group::insert(group, &rec).map_err(|e| {
@@ -399,6 +686,35 @@ fn insert(mut rec: Node<Record>, group: &Node<Group>) -> Result {
     })
 }
 
+/// Returns `recs` without the records contained within other groups of `recs`.
+///
+/// Removing a group also removes (and erases) its members, so they must not be
+/// removed again.
+fn outermost(recs: Vec<Node<Record>>) -> Vec<Node<Record>> {
+    let is_removed = |group: &Node<Group>| recs.iter().any(|r| {
+        matches!(&*r.borrow(), Record::Group(g) if Rc::ptr_eq(g, group))
+    });
+
+    let is_outermost = |rec: &Node<Record>| {
+        let mut parent = rec.borrow().parent();
+
+        while let Some(group) = parent {
+            if is_removed(&group) {
+                return false;
+            }
+
+            parent = group.borrow().parent();
+        }
+
+        true
+    };
+
+    recs.iter()
+        .filter(|r| is_outermost(r))
+        .cloned()
+        .collect()
+}
+
 fn clone_name(group: &Node<Group>) -> String {
     group.borrow().name().to_owned()
 }
@@ -0,0 +1,173 @@
+use crate::util::proc::{self, Pid};
+
+use std::{
+    fs,
+    io,
+    thread
+};
+
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    time::{Duration, Instant}
+};
+
+use std::os::fd::AsRawFd;
+
+use nix::errno::Errno;
+
+use nix::fcntl::{self, FlockArg};
+
+use super::CLEAR_SIGNAL;
+
+/// The maximum time to wait for holders to stop.
+const STOP_TIME: Duration = Duration::from_secs(2);
+/// How often to check whether holders stopped.
+const POLL_TIME: Duration = Duration::from_millis(20);
+
+/// The registration of the current process as a clipboard holder, undone when
+/// dropped.
+///
+/// Holders are registered with a file named after their PID, which they keep
+/// locked as long as they live. This way, registrations left behind by holders
+/// which were killed are recognised, even if their PID was reused.
+pub struct Holder {
+    path: PathBuf,
+    /// Holds the lock.
+    _file: File
+}
+
+pub type Error = io::Error;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Holder {
+    /// Stops the holders registered in `dir` (see [`stop_all`]) and registers
+    /// the current process instead.
+    pub fn replace_in(dir: &Path) -> Result<Self> {
+        use std::os::unix::fs::DirBuilderExt;
+
+        stop_all(dir)?;
+
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)?;
+
+        let path = dir.join(std::process::id().to_string());
+        // Not recognised as a registration until locked.
+        let new_path = path.with_extension("new");
+        let file = File::create(&new_path)?;
+
+        fcntl::flock(file.as_raw_fd(), FlockArg::LockExclusiveNonblock)?;
+        fs::rename(new_path, &path)?;
+
+        Ok(Self { path, _file: file })
+    }
+}
+
+impl Drop for Holder {
+    fn drop(&mut self) {
+        // A registration left behind is recognised as such by other processes.
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Makes every holder registered in `dir` clear the clipboard and exit, and
+/// waits a short time for them to do so.
+///
+/// Returns the number of holders signalled.
+pub fn stop_all(dir: &Path) -> Result<usize> {
+    let holders = live_in(dir)?;
+
+    for (pid, _) in &holders {
+        match proc::send_signal(*pid, CLEAR_SIGNAL) {
+            Ok(()) | Err(Errno::ESRCH) => (),
+            Err(e) => return Err(e.into())
+        }
+    }
+
+    let deadline = Instant::now() + STOP_TIME;
+
+    for (_, file) in &holders {
+        while is_locked(file)? && Instant::now() < deadline {
+            thread::sleep(POLL_TIME);
+        }
+    }
+
+    Ok(holders.len())
+}
+
+/// Returns the PIDs of the live holders registered in `dir` with their open
+/// registration files, and removes the registrations left behind.
+///
+/// Registrations still being created by holders which were killed are removed
+/// too. Entries disappearing in the meantime are ignored, as they are removed
+/// by holders when exiting, and by other processes calling this.
+fn live_in(dir: &Path) -> Result<Vec<(Pid, File)>> {
+    use io::ErrorKind::NotFound;
+
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(e) if e.kind() == NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e)
+    };
+
+    let mut result = Vec::new();
+
+    for entry in entries {
+        let path = entry?.path();
+
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+
+        // See `Holder::replace_in`.
+        let (pid, pending) = match name.strip_suffix(".new") {
+            Some(pid) => (pid, true),
+            None => (name, false)
+        };
+
+        let Ok(pid) = pid.parse().map(Pid::from_raw) else {
+            continue;
+        };
+
+        let file = match File::open(&path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == NotFound => continue,
+            Err(e) => return Err(e)
+        };
+
+        if is_locked(&file)? {
+            result.push((pid, file));
+            continue;
+        }
+
+        // A pending registration may not be locked yet.
+        if pending && proc::exists(pid) {
+            continue;
+        }
+
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != NotFound => return Err(e),
+            _ => ()
+        }
+    }
+
+    Ok(result)
+}
+
+/// Returns whether the registration `file` is locked by a live holder.
+fn is_locked(file: &File) -> Result<bool> {
+    let fd = file.as_raw_fd();
+
+    match fcntl::flock(fd, FlockArg::LockSharedNonblock) {
+        Ok(()) => {
+            fcntl::flock(fd, FlockArg::Unlock)?;
+            Ok(false)
+        }
+
+        Err(Errno::EWOULDBLOCK) => Ok(true),
+        Err(e) => Err(e.into())
+    }
+}
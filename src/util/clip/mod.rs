@@ -0,0 +1,460 @@
+pub mod holders;
+
+mod osc52;
+mod x11;
+
+pub use holders::Holder;
+
+use super::secret::Secret;
+
+use std::{
+    fmt,
+    thread
+};
+
+use std::path::Path;
+
+use std::fmt::Display;
+
+use std::{
+    num::NonZeroUsize,
+    time::{Duration, Instant},
+    sync::atomic::{AtomicBool, Ordering}
+};
+
+use arboard::LinuxClipboardKind;
+
+use nix::sys::signal::Signal;
+
+/// The signal making a process holding several texts move on to the next one.
+/// See [`Clipboard::hold`].
+pub const SKIP_SIGNAL: Signal = Signal::SIGUSR1;
+
+/// The signal making a process holding text clear the clipboard and exit
+/// immediately. See [`Clipboard::hold`].
+pub const CLEAR_SIGNAL: Signal = Signal::SIGTERM;
+
+/// How often to check whether a signal was received while sleeping.
+const POLL_TIME: Duration = Duration::from_millis(100);
+
+/// Set when [`SKIP_SIGNAL`] is received.
+static SKIPPED: AtomicBool = AtomicBool::new(false);
+/// Set when [`CLEAR_SIGNAL`] is received.
+static CLEARED: AtomicBool = AtomicBool::new(false);
+
+pub struct Clipboard {
+    backend: Backend,
+    /// The registration of this process as a holder, if any.
+    holder: Option<Holder>
+}
+
+/// The means of accessing the clipboard.
+enum Backend {
+    /// Through the X11 display.
+    Display(arboard::Clipboard),
+    /// Through the terminal, which is useful in remote sessions. See
+    /// [`osc52`].
+    Terminal(osc52::Terminal)
+}
+
+/// The selections to copy to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    /// Pasted with the middle mouse button.
+    Primary,
+    /// Pasted with an explicit command like Ctrl+V.
+    Clipboard,
+    Both
+}
+
+/// How text is held in the clipboard.
+#[derive(Clone, Copy)]
+pub struct HoldOpts {
+    pub time: Duration,
+    pub selection: Selection,
+    /// Whether to restore the previous contents of the selections afterwards.
+    pub restore: bool,
+    /// The number of times the text may be pasted, or `None` if unlimited.
+    pub pastes: Option<NonZeroUsize>,
+    /// Whether to copy through the terminal even if a display is available.
+    pub terminal: bool
+}
+
+#[allow(clippy::enum_variant_names)]
+pub enum Error {
+    AccessingClipboard(arboard::Error),
+    SettingClipboard(arboard::Error),
+    ClearingClipboard(arboard::Error),
+    ServingClipboard(x11::Error),
+    WritingTerminal(osc52::Error),
+    Registering(holders::Error)
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Clipboard {
+    /// Accesses the clipboard, with the current process replacing the holders
+    /// registered in `holders_dir` if specified. See [`Holder::replace_in`].
+    ///
+    /// The terminal is used if `terminal` is set or if there is no display.
+    pub fn new(holders_dir: Option<&Path>, terminal: bool) -> Result<Self> {
+        let holder = holders_dir
+            .map(Holder::replace_in)
+            .transpose()
+            .map_err(Error::Registering)?;
+
+        let backend = if terminal || !has_display() {
+            Backend::Terminal(
+                osc52::Terminal::open()
+                    .map_err(Error::WritingTerminal)?
+            )
+        } else {
+            Backend::Display(
+                arboard::Clipboard::new()
+                    .map_err(Error::AccessingClipboard)?
+            )
+        };
+
+        Ok(Self { backend, holder })
+    }
+
+    /// Copies each of `texts` in turn to the selections of `opts` and keeps it
+    /// there for `opts.time`, or until it was pasted `opts.pastes` times. In
+    /// the latter case, the selections are served by this process (see
+    /// [`x11::serve`]).
+    ///
+    /// With several texts, each is only pasted once unless specified otherwise,
+    /// and receiving [`SKIP_SIGNAL`] moves on to the next one immediately.
+    /// Receiving [`CLEAR_SIGNAL`] ends the hold immediately, and the
+    /// selections are cleared without being restored. Both signals must be
+    /// caught with [`catch_signals`] beforehand.
+    ///
+    /// Afterwards, the selections still holding the last text are cleared, or
+    /// given their previous contents back if `opts.restore` is set. Those
+    /// changed in the meantime are left alone. Restored contents are kept
+    /// available until replaced, as they would otherwise be lost when this
+    /// process exits.
+    ///
+    /// Through the terminal, pastes cannot be counted and the selections cannot
+    /// be read, so each text is held for `opts.time` or until skipped, and the
+    /// selections are always cleared. The clipboard selection is always used,
+    /// as many terminals support no other.
+    pub fn hold(&mut self, texts: &[&str], opts: HoldOpts) -> Result<()> {
+        use arboard::{SetExtLinux, ClearExtLinux};
+
+        let Some(&text) = texts.last() else {
+            return Ok(());
+        };
+
+        let inner = match &mut self.backend {
+            Backend::Display(c) => c,
+
+            Backend::Terminal(term) => {
+                let result = hold_terminal(term, texts, opts);
+                self.holder = None;
+
+                return result;
+            }
+        };
+
+        let kinds = opts.selection.kinds();
+
+        // Moving on to the next text requires counting pastes.
+        let pastes = match texts {
+            [_] => opts.pastes,
+            _ => opts.pastes.or(NonZeroUsize::new(1))
+        };
+
+        // Selections which are empty or don't contain text are cleared.
+        let previous = kinds.iter()
+            .map(|k| opts.restore.then(|| get(inner, *k)).flatten())
+            .collect::<Vec<_>>();
+
+        let kept = match pastes {
+            Some(pastes) => {
+                let mut kept = Vec::new();
+
+                for t in texts {
+                    kept = x11::serve(
+                        t, &kinds, pastes, opts.time, stop_requested
+                    ).map_err(Error::ServingClipboard)?;
+
+                    if CLEARED.load(Ordering::SeqCst) {
+                        break;
+                    }
+                }
+
+                kept
+            }
+
+            None => {
+                for kind in &kinds {
+                    inner.set()
+                        .clipboard(*kind)
+                        .text(text)
+                        .map_err(Error::SettingClipboard)?;
+                }
+
+                sleep_until(opts.time, stop_requested);
+
+                kinds.iter()
+                    .map(|k| holds(inner, *k, text))
+                    .collect()
+            }
+        };
+
+        let cleared = CLEARED.load(Ordering::SeqCst);
+        let mut restored = Vec::new();
+
+        for ((kind, prev), kept) in kinds.into_iter().zip(previous).zip(kept) {
+            if !kept {
+                continue;
+            }
+
+            match prev.filter(|_| !cleared) {
+                Some(prev) => {
+                    inner.set()
+                        .clipboard(kind)
+                        .text(prev.as_str())
+                        .map_err(Error::SettingClipboard)?;
+
+                    restored.push((kind, prev));
+                }
+
+                // Released selections are already empty.
+                None if pastes.is_some() => (),
+
+                None => inner.clear_with()
+                    .clipboard(kind)
+                    .map_err(Error::ClearingClipboard)?
+            }
+        }
+
+        // The secret is gone, so other holders need not stop this process.
+        self.holder = None;
+
+        for (kind, prev) in restored {
+            if !holds(inner, kind, &prev) {
+                continue;
+            }
+
+            // Blocks until another program takes ownership of the selection.
+            inner.set()
+                .wait()
+                .clipboard(kind)
+                .text(prev.as_str())
+                .map_err(Error::SettingClipboard)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Holds each of `texts` in turn through the terminal `term`, as described in
+/// [`Clipboard::hold`].
+fn hold_terminal(
+    term: &mut osc52::Terminal,
+    texts: &[&str],
+    opts: HoldOpts
+) -> Result<()> {
+    for text in texts {
+        term.set(opts.selection, text)
+            .map_err(Error::WritingTerminal)?;
+
+        sleep_until(opts.time, stop_requested);
+
+        if CLEARED.load(Ordering::SeqCst) {
+            break;
+        }
+    }
+
+    term.clear(opts.selection)
+        .map_err(Error::WritingTerminal)
+}
+
+/// Returns the text in the selection `kind` of `clip`, if any.
+fn get(
+    clip: &mut arboard::Clipboard,
+    kind: LinuxClipboardKind
+) -> Option<Secret<String>> {
+    use arboard::GetExtLinux;
+
+    clip.get()
+        .clipboard(kind)
+        .text()
+        .ok()
+        .map(Secret::new)
+}
+
+/// Returns whether the selection `kind` of `clip` contains `text`.
+fn holds(
+    clip: &mut arboard::Clipboard,
+    kind: LinuxClipboardKind,
+    text: &str
+) -> bool {
+    get(clip, kind).is_some_and(|t| t.as_str() == text)
+}
+
+/// Returns whether [`SKIP_SIGNAL`] was received since the last call, or
+/// whether [`CLEAR_SIGNAL`] was ever received.
+fn stop_requested() -> bool {
+    SKIPPED.swap(false, Ordering::SeqCst) || CLEARED.load(Ordering::SeqCst)
+}
+
+/// Returns whether an X11 display is available.
+///
+/// Wayland sessions only count if they run XWayland, since the clipboard is
+/// only accessed through X11.
+fn has_display() -> bool {
+    std::env::var_os("DISPLAY").is_some_and(|d| !d.is_empty())
+}
+
+/// Makes [`SKIP_SIGNAL`] and [`CLEAR_SIGNAL`] affect [`Clipboard::hold`]
+/// instead of terminating the process, and unblocks them if they were blocked
+/// by [`block_signals`].
+///
+/// Signals received before holding starts are remembered, so this should be
+/// called as early as possible in holder processes.
+pub fn catch_signals() {
+    use nix::sys::signal::{self, SigmaskHow};
+
+    catch(SKIP_SIGNAL);
+    catch(CLEAR_SIGNAL);
+
+    // Signals that were pending are delivered to the handlers now. Only fails
+    // with an invalid `how`.
+    signal::sigprocmask(SigmaskHow::SIG_UNBLOCK, Some(&held_signals()), None)
+        .unwrap();
+}
+
+/// Blocks [`SKIP_SIGNAL`] and [`CLEAR_SIGNAL`] in the calling thread, so that
+/// they stay pending until [`catch_signals`] is called.
+///
+/// The signal mask is kept across `exec`, so this is meant to be called in
+/// holder processes before they are executed, which would otherwise be
+/// terminated by signals received before catching them. It is
+/// async-signal-safe.
+pub fn block_signals() -> nix::Result<()> {
+    use nix::sys::signal::{self, SigmaskHow};
+
+    signal::sigprocmask(SigmaskHow::SIG_BLOCK, Some(&held_signals()), None)
+}
+
+/// Returns the set of [`SKIP_SIGNAL`] and [`CLEAR_SIGNAL`].
+fn held_signals() -> nix::sys::signal::SigSet {
+    let mut result = nix::sys::signal::SigSet::empty();
+
+    result.add(SKIP_SIGNAL);
+    result.add(CLEAR_SIGNAL);
+
+    result
+}
+
+/// Sets [`SKIPPED`] or [`CLEARED`] whenever `sig` is received.
+fn catch(sig: Signal) {
+    use nix::sys::signal::{self, SigAction, SigHandler, SaFlags, SigSet};
+
+    extern "C" fn on_signal(sig: nix::libc::c_int) {
+        match Signal::try_from(sig) {
+            Ok(SKIP_SIGNAL) => SKIPPED.store(true, Ordering::SeqCst),
+            Ok(CLEAR_SIGNAL) => CLEARED.store(true, Ordering::SeqCst),
+            _ => ()
+        }
+    }
+
+    let action = SigAction::new(
+        SigHandler::Handler(on_signal),
+        SaFlags::empty(),
+        SigSet::empty()
+    );
+
+    // SAFETY: The handler only stores to atomics, which is async-signal-safe.
+    // Without `SA_RESTART`, it also interrupts waits for events.
+    unsafe {
+        // Only fails with an invalid signal.
+        signal::sigaction(sig, &action).unwrap();
+    }
+}
+
+/// Sleeps for `time`, or until `stop` returns true.
+fn sleep_until(time: Duration, stop: impl Fn() -> bool) {
+    let deadline = Instant::now() + time;
+
+    while !stop() {
+        let left = deadline.saturating_duration_since(Instant::now());
+
+        if left.is_zero() {
+            break;
+        }
+
+        thread::sleep(left.min(POLL_TIME));
+    }
+}
+
+impl Selection {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "primary" => Some(Self::Primary),
+            "clipboard" => Some(Self::Clipboard),
+            "both" => Some(Self::Both),
+            _ => None
+        }
+    }
+
+    fn kinds(self) -> Vec<LinuxClipboardKind> {
+        use LinuxClipboardKind as Kind;
+
+        match self {
+            Self::Primary => vec![Kind::Primary],
+            Self::Clipboard => vec![Kind::Clipboard],
+            Self::Both => vec![Kind::Primary, Kind::Clipboard]
+        }
+    }
+}
+
+impl Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Primary => "primary",
+            Self::Clipboard => "clipboard",
+            Self::Both => "both"
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl Default for HoldOpts {
+    fn default() -> Self {
+        use crate::config;
+
+        Self {
+            time: Duration::from_secs(config::DEFAULT_CLIP_TIME),
+            selection: config::DEFAULT_CLIP_SELECTION,
+            restore: config::RESTORE_CLIPBOARD,
+            pastes: None,
+            terminal: config::CLIP_THROUGH_TERMINAL
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Error::*;
+
+        match self {
+            AccessingClipboard(e) =>
+                write!(f, "cannot access clipboard: {e}"),
+            SettingClipboard(e) =>
+                write!(f, "cannot set clipboard: {e}"),
+            ClearingClipboard(e) =>
+                write!(f, "cannot clear clipboard: {e}"),
+            ServingClipboard(e) =>
+                write!(f, "cannot hold clipboard: {e}"),
+            WritingTerminal(e) =>
+                write!(f, "cannot copy through terminal: {e}"),
+            Registering(e) =>
+                write!(f, "cannot register clipboard process: {e}")
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use super::Selection;
+
+use crate::util::secret::Secret;
+
+use std::io;
+
+use std::{
+    fs::{File, OpenOptions},
+    io::Write
+};
+
+/// The controlling terminal of the current process, copying text written to it
+/// in OSC 52 escape sequences.
+///
+/// Supported by most terminal emulators, and working over SSH since the
+/// sequences travel with the rest of the output. Some terminals need to be
+/// configured to allow it.
+pub struct Terminal(File);
+
+pub type Error = io::Error;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Terminal {
+    /// Opens the controlling terminal, which remains usable even if standard
+    /// output is redirected.
+    pub fn open() -> Result<Self> {
+        Ok(Self(OpenOptions::new().write(true).open("/dev/tty")?))
+    }
+
+    /// Copies `text` to `selection`.
+    pub fn set(&mut self, selection: Selection, text: &str) -> Result<()> {
+        use base64::Engine;
+        use base64::engine::general_purpose::STANDARD;
+
+        const START: &str = "\x1b]52;";
+        const END: &str = "\x07";
+
+        let encoded = Secret::new(STANDARD.encode(text));
+        let targets = targets(selection);
+
+        // Sized in advance so that it is never reallocated, which would leave
+        // unerased copies of the text behind.
+        let len = START.len() + targets.len() + 1 + encoded.len() + END.len();
+        let mut seq = Secret::new(String::with_capacity(len));
+
+        seq.push_str(START);
+        seq.push_str(targets);
+        seq.push(';');
+        seq.push_str(&encoded);
+        seq.push_str(END);
+
+        self.0.write_all(seq.as_bytes())?;
+        self.0.flush()
+    }
+
+    /// Empties `selection`.
+    pub fn clear(&mut self, selection: Selection) -> Result<()> {
+        self.set(selection, "")
+    }
+}
+
+/// Returns the OSC 52 parameter designating `selection`.
+///
+/// Many terminals ignore every selection but the clipboard, so it is always
+/// included.
+fn targets(selection: Selection) -> &'static str {
+    match selection {
+        Selection::Primary | Selection::Both => "pc",
+        Selection::Clipboard => "c"
+    }
+}
@@ -0,0 +1,299 @@
+use std::fmt;
+
+use std::fmt::Display;
+
+use std::{
+    num::NonZeroUsize,
+    time::{Duration, Instant}
+};
+
+use arboard::LinuxClipboardKind;
+
+use x11rb::{
+    connection::Connection,
+    rust_connection::RustConnection,
+    wrapper::ConnectionExt as _,
+    protocol::Event
+};
+
+use x11rb::protocol::xproto::{
+    self,
+    Atom,
+    AtomEnum,
+    ConnectionExt as _
+};
+
+use x11rb::errors::{
+    ConnectError,
+    ConnectionError,
+    ReplyError,
+    ReplyOrIdError
+};
+
+/// The text formats we offer, other than `STRING` which is predefined.
+const TEXT_TARGETS: [&str; 3] = [
+    "UTF8_STRING", "TEXT", "text/plain;charset=utf-8"
+];
+
+pub enum Error {
+    Connecting(ConnectError),
+    Communicating(ReplyOrIdError),
+    /// Another program took ownership of a selection before us.
+    NotOwner
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A connection to the X server owning some selections.
+struct Owner {
+    conn: RustConnection,
+    /// The selections still owned, indexed like those passed to `serve`.
+    owned: Vec<Option<Atom>>,
+    targets: Atom,
+    text_targets: Vec<Atom>
+}
+
+/// Owns the selections `kinds` and serves `text` to the programs requesting
+/// it, until it was pasted `pastes` times, `time` runs out or `stop` returns
+/// true. `stop` is called whenever an event or signal is received.
+///
+/// Only actual transfers of `text` count as pastes, but note that clipboard
+/// managers may request it as soon as it is copied. Ownership of the
+/// selections is released afterwards, leaving them empty.
+///
+/// Returns whether each selection was still owned at the end, which is not the
+/// case for those taken over by another program.
+pub fn serve(
+    text: &str,
+    kinds: &[LinuxClipboardKind],
+    pastes: NonZeroUsize,
+    time: Duration,
+    stop: impl Fn() -> bool
+) -> Result<Vec<bool>> {
+    let deadline = Instant::now() + time;
+    let mut owner = Owner::new(kinds)?;
+    let mut remaining = pastes.get();
+
+    while remaining > 0 && owner.owns_any() {
+        if stop() {
+            break;
+        }
+
+        owner.conn.flush()?;
+
+        let Some(event) = owner.conn.poll_for_event()? else {
+            let left = deadline.saturating_duration_since(Instant::now());
+
+            if left.is_zero() {
+                break;
+            }
+
+            // A signal received right before waiting doesn't interrupt it, so
+            // `stop` is checked regularly, as with `sleep_until`.
+            owner.wait_readable(left.min(super::POLL_TIME));
+            continue;
+        };
+
+        match event {
+            Event::SelectionRequest(req) => {
+                let pasted = owner.answer(&req, text)?;
+                remaining -= usize::from(pasted);
+            }
+
+            Event::SelectionClear(clear) => owner.owned.iter_mut()
+                .filter(|s| **s == Some(clear.selection))
+                .for_each(|s| *s = None),
+
+            _ => ()
+        }
+    }
+
+    owner.release()
+}
+
+impl Owner {
+    fn new(kinds: &[LinuxClipboardKind]) -> Result<Self> {
+        use xproto::{CreateWindowAux, WindowClass};
+
+        use x11rb::COPY_FROM_PARENT;
+
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let root = conn.setup().roots[screen_num].root;
+        let win = conn.generate_id()?;
+
+        // The window is never mapped, and only used to own the selections.
+        conn.create_window(
+            COPY_FROM_PARENT as u8, win, root,
+            0, 0, 1, 1, 0,
+            WindowClass::INPUT_ONLY, COPY_FROM_PARENT,
+            &CreateWindowAux::new()
+        )?;
+
+        let intern = |name: &str| -> Result<Atom> {
+            Ok(conn.intern_atom(false, name.as_bytes())?.reply()?.atom)
+        };
+
+        let owned = kinds.iter()
+            .map(|k| Ok(Some(match k {
+                LinuxClipboardKind::Primary => AtomEnum::PRIMARY.into(),
+                LinuxClipboardKind::Secondary => AtomEnum::SECONDARY.into(),
+                LinuxClipboardKind::Clipboard => intern("CLIPBOARD")?
+            })))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut text_targets = TEXT_TARGETS.iter()
+            .map(|t| intern(t))
+            .collect::<Result<Vec<_>>>()?;
+
+        text_targets.push(AtomEnum::STRING.into());
+
+        let targets = intern("TARGETS")?;
+
+        for sel in owned.iter().flatten() {
+            conn.set_selection_owner(win, *sel, x11rb::CURRENT_TIME)?;
+
+            if conn.get_selection_owner(*sel)?.reply()?.owner != win {
+                return Err(Error::NotOwner);
+            }
+        }
+
+        Ok(Self { conn, owned, targets, text_targets })
+    }
+
+    fn owns_any(&self) -> bool {
+        self.owned.iter().any(Option::is_some)
+    }
+
+    /// Answers the selection request `req` with `text` or the formats it is
+    /// available in.
+    ///
+    /// Returns whether `text` was transferred.
+    fn answer(
+        &self,
+        req: &xproto::SelectionRequestEvent,
+        text: &str
+    ) -> Result<bool> {
+        use xproto::{PropMode, EventMask, SelectionNotifyEvent};
+
+        // Obsolete clients may not specify a property.
+        let property = match req.property {
+            p if p == u32::from(AtomEnum::NONE) => req.target,
+            p => p
+        };
+
+        let known = self.owned.contains(&Some(req.selection));
+        let mut transferred = false;
+
+        let property = if known && req.target == self.targets {
+            let mut targets = vec![self.targets];
+            targets.extend(&self.text_targets);
+
+            self.conn.change_property32(
+                PropMode::REPLACE, req.requestor, property,
+                AtomEnum::ATOM, &targets
+            )?;
+
+            property
+        } else if known && self.text_targets.contains(&req.target) {
+            self.conn.change_property8(
+                PropMode::REPLACE, req.requestor, property,
+                req.target, text.as_bytes()
+            )?;
+
+            transferred = true;
+            property
+        } else {
+            // Refuse the request.
+            AtomEnum::NONE.into()
+        };
+
+        let notify = SelectionNotifyEvent {
+            response_type: xproto::SELECTION_NOTIFY_EVENT,
+            sequence: 0,
+            time: req.time,
+            requestor: req.requestor,
+            selection: req.selection,
+            target: req.target,
+            property
+        };
+
+        let mask = EventMask::NO_EVENT;
+        self.conn.send_event(false, req.requestor, mask, notify)?;
+        self.conn.flush()?;
+
+        Ok(transferred)
+    }
+
+    /// Waits at most `time` for data from the X server.
+    fn wait_readable(&self, time: Duration) {
+        use nix::poll::{self, PollFd, PollFlags};
+
+        use std::os::fd::{AsRawFd, BorrowedFd};
+
+        // SAFETY: the file descriptor stays open as long as the connection.
+        let fd = unsafe {
+            BorrowedFd::borrow_raw(self.conn.stream().as_raw_fd())
+        };
+
+        let millis = time.as_millis()
+            .try_into()
+            .unwrap_or(i32::MAX);
+
+        // An interrupted wait is handled like any other wakeup.
+        let _ = poll::poll(&mut [PollFd::new(&fd, PollFlags::POLLIN)], millis);
+    }
+
+    /// Releases ownership of the selections still owned.
+    ///
+    /// Returns which selections were owned.
+    fn release(self) -> Result<Vec<bool>> {
+        for sel in self.owned.iter().flatten() {
+            self.conn.set_selection_owner(
+                x11rb::NONE, *sel, x11rb::CURRENT_TIME
+            )?;
+        }
+
+        self.conn.flush()?;
+
+        Ok(self.owned.iter().map(Option::is_some).collect())
+    }
+}
+
+impl From<ConnectError> for Error {
+    fn from(e: ConnectError) -> Self {
+        Self::Connecting(e)
+    }
+}
+
+impl From<ReplyOrIdError> for Error {
+    fn from(e: ReplyOrIdError) -> Self {
+        Self::Communicating(e)
+    }
+}
+
+impl From<ReplyError> for Error {
+    fn from(e: ReplyError) -> Self {
+        Self::Communicating(e.into())
+    }
+}
+
+impl From<ConnectionError> for Error {
+    fn from(e: ConnectionError) -> Self {
+        Self::Communicating(e.into())
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Error::*;
+
+        match self {
+            Connecting(e) =>
+                write!(f, "cannot connect to X server: {e}"),
+            Communicating(e) =>
+                write!(f, "X server error: {e}"),
+            NotOwner =>
+                write!(f, "cannot own selection")
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use super::secret::Secret;
+
 use std::{
     fs,
     io,
@@ -52,7 +54,7 @@ impl SafePath {
     ///
     /// This is a convenience function equivalent to
     /// `SafePath::main().display()`.
-    pub fn display(&self) -> path::Display {
+    pub fn display(&self) -> path::Display<'_> {
         self.main.display()
     }
 
@@ -132,6 +134,39 @@ pub fn clear(f: &mut File) -> Result<()> {
     f.rewind()
 }
 
+/// Reads the entire contents of the file at `path`.
+///
+/// The returned buffer is allocated with the size of the file in advance, so
+/// that it is not reallocated while reading (which could leave unerased copies
+/// of sensitive data in memory).
+pub fn read_all<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut f = File::open(path)?;
+    let len = f.metadata()?.len() as usize;
+    let mut result = Secret::new(Vec::with_capacity(len));
+
+    f.read_to_end(&mut result)?;
+
+    Ok(result.into_inner())
+}
+
+/// Writes `data` to a new file at `path`, only accessible by its owner (with
+/// mode 0600).
+///
+/// Fails if the file already exists.
+pub fn write_private<P: AsRef<Path>>(path: P, data: &[u8]) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    File::options()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?
+        .write_all(data)
+}
+
 /// Returns a file name suitable for a backup of `file_path`.
 ///
 /// Same unicity conditions as [`file_name_from`].
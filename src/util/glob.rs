@@ -0,0 +1,103 @@
+use crate::find::RecordPath;
+
+use std::borrow::Cow;
+
+/// A component of a glob pattern.
+#[derive(Clone, Copy)]
+pub(crate) enum GlobToken {
+    AnyChars,
+    AnyChar,
+    Char(char)
+}
+
+/// Verifies if `name` matches the glob pattern `pat`, which is in its escaped
+/// form.
+pub(crate) fn glob_matches(pat: &str, name: &str) -> bool {
+    use GlobToken::*;
+
+    let pat = glob_tokens(pat).collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+
+    let (mut p, mut n) = (0, 0);
+    // The position of the last `AnyChars` in `pat`, and of the character in
+    // `name` from which it currently matches.
+    let mut backtrack = Option::<(usize, usize)>::None;
+
+    while n < name.len() {
+        match pat.get(p) {
+            Some(AnyChars) => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+
+            Some(AnyChar) => (p, n) = (p + 1, n + 1),
+            Some(Char(c)) if *c == name[n] => (p, n) = (p + 1, n + 1),
+
+            // Let the last `AnyChars` match one more character, and retry.
+            _ => match backtrack {
+                Some((bp, bn)) => {
+                    backtrack = Some((bp, bn + 1));
+                    (p, n) = (bp + 1, bn + 1);
+                }
+
+                None => return false
+            }
+        }
+    }
+
+    pat[p..].iter().all(|t| matches!(t, AnyChars))
+}
+
+/// Returns the components of the glob pattern `pat`, which is in its escaped
+/// form.
+pub(crate) fn glob_tokens(pat: &str) -> impl Iterator<Item = GlobToken> + '_ {
+    let mut chars = pat.chars();
+
+    std::iter::from_fn(move || {
+        Some(match chars.next()? {
+            // As with `unescape`.
+            RecordPath::ESCAPE => GlobToken::Char(
+                chars.next().unwrap_or(RecordPath::ESCAPE)
+            ),
+
+            RecordPath::ANY_CHARS => GlobToken::AnyChars,
+            RecordPath::ANY_CHAR => GlobToken::AnyChar,
+            c => GlobToken::Char(c)
+        })
+    })
+}
+
+/// Verifies if `c` must be escaped within a record path element to be matched
+/// literally.
+pub(crate) fn is_special(c: char) -> bool {
+    matches!(
+        c,
+        RecordPath::DELIM | RecordPath::ESCAPE |
+        RecordPath::ANY_CHARS | RecordPath::ANY_CHAR
+    )
+}
+
+/// Returns `elem` with its escape sequences replaced by the characters they
+/// represent.
+///
+/// As this function never fails, a single trailing escape character is kept
+/// unchanged if it exists.
+pub(crate) fn unescape(elem: &str) -> Cow<'_, str> {
+    if !elem.contains(RecordPath::ESCAPE) {
+        return Cow::Borrowed(elem);
+    }
+
+    let mut result = String::with_capacity(elem.len());
+    let mut chars = elem.chars();
+
+    while let Some(c) = chars.next() {
+        if c == RecordPath::ESCAPE {
+            // Handle the single trailing escape character.
+            result.push(chars.next().unwrap_or(c));
+        } else {
+            result.push(c);
+        }
+    }
+
+    Cow::Owned(result)
+}
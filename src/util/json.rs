@@ -0,0 +1,137 @@
+use std::{fmt, io};
+
+use std::io::Write;
+
+/// Writes JSON values to an output as they are produced.
+///
+/// Strings are escaped while being written, so sensitive data is never copied
+/// into intermediate buffers other than that of the output. Separators are
+/// inserted automatically between the elements of arrays and objects.
+pub struct JsonWriter<W: Write> {
+    out: W,
+    /// Whether an element was written to each open array or object.
+    nonempty: Vec<bool>,
+    /// Whether a key was just written, and is waiting for its value.
+    after_key: bool
+}
+
+pub type Result<T> = io::Result<T>;
+
+impl<W: Write> JsonWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self { out, nonempty: Vec::new(), after_key: false }
+    }
+
+    pub fn begin_array(&mut self) -> Result<()> {
+        self.open(b"[")
+    }
+
+    pub fn end_array(&mut self) -> Result<()> {
+        self.close(b"]")
+    }
+
+    pub fn begin_object(&mut self) -> Result<()> {
+        self.open(b"{")
+    }
+
+    pub fn end_object(&mut self) -> Result<()> {
+        self.close(b"}")
+    }
+
+    /// Writes the key of the next member of the current object.
+    pub fn key(&mut self, key: &str) -> Result<()> {
+        self.separate()?;
+        write_escaped(&mut self.out, key)?;
+        self.out.write_all(b":")?;
+        self.after_key = true;
+
+        Ok(())
+    }
+
+    pub fn string(&mut self, s: &str) -> Result<()> {
+        self.separate()?;
+        write_escaped(&mut self.out, s)
+    }
+
+    /// Writes a number, which must be finite if it is a float.
+    pub fn number<N: fmt::Display>(&mut self, n: N) -> Result<()> {
+        self.separate()?;
+        write!(self.out, "{n}")
+    }
+
+    /// Writes a member of the current object with a string value.
+    pub fn member(&mut self, key: &str, val: &str) -> Result<()> {
+        self.key(key)?;
+        self.string(val)
+    }
+
+    /// Ends the output with a newline and flushes it.
+    pub fn finish(mut self) -> Result<()> {
+        self.out.write_all(b"\n")?;
+        self.out.flush()
+    }
+
+    fn open(&mut self, delim: &[u8]) -> Result<()> {
+        self.separate()?;
+        self.out.write_all(delim)?;
+        self.nonempty.push(false);
+
+        Ok(())
+    }
+
+    fn close(&mut self, delim: &[u8]) -> Result<()> {
+        self.nonempty.pop();
+        self.out.write_all(delim)
+    }
+
+    /// Writes a comma if an element precedes the next one in the current array
+    /// or object, unless it is the value of a key.
+    fn separate(&mut self) -> Result<()> {
+        if std::mem::take(&mut self.after_key) {
+            return Ok(());
+        }
+
+        if let Some(nonempty) = self.nonempty.last_mut() {
+            if std::mem::replace(nonempty, true) {
+                self.out.write_all(b",")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes `s` to `out` as a JSON string.
+///
+/// Unescaped runs of `s` are written as they are, without being copied.
+fn write_escaped<W: Write>(out: &mut W, s: &str) -> Result<()> {
+    out.write_all(b"\"")?;
+
+    let mut start = 0;
+
+    for (i, b) in s.bytes().enumerate() {
+        // Multi-byte characters never contain ASCII bytes, so they are never
+        // split. Control characters without a short escape use their code.
+        let escape = match b {
+            b'"' => Some("\\\""),
+            b'\\' => Some("\\\\"),
+            b'\n' => Some("\\n"),
+            b'\r' => Some("\\r"),
+            b'\t' => Some("\\t"),
+            0x00..=0x1f => None,
+            _ => continue
+        };
+
+        out.write_all(&s.as_bytes()[start..i])?;
+
+        match escape {
+            Some(e) => out.write_all(e.as_bytes())?,
+            None => write!(out, "\\u{b:04x}")?
+        }
+
+        start = i + 1;
+    }
+
+    out.write_all(&s.as_bytes()[start..])?;
+    out.write_all(b"\"")
+}
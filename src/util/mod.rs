@@ -1,7 +1,10 @@
 pub mod clip;
 pub mod crypt;
 pub mod file;
+pub mod glob;
+pub mod json;
 pub mod proc;
+pub mod qr;
 pub mod record;
 pub mod secret;
 pub mod user_io;
@@ -1,41 +1,19 @@
-use nix::{
-    sys::{mman, resource},
-    unistd
-};
+use nix::sys::{mman, resource};
 
 use nix::sys::{
     mman::MlockAllFlags,
     resource::Resource
 };
 
-use nix::unistd::{ForkResult, Pid};
+pub use nix::unistd::Pid;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
-// Our version of `ForkResult` has a `must_use` attribute, which encourages the
-// user to handle it. This is vital, as the child process is likely intended to
-// follow a different path of execution.
-#[must_use = "the currently executing process could be the child or parent"]
-pub enum Process {
-    Child,
-    Parent { child: Pid }
-}
+use nix::sys::signal::Signal;
 
 pub type Error = nix::Error;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-impl From<ForkResult> for Process {
-    fn from(f: ForkResult) -> Self {
-        use ForkResult::{Child, Parent};
-
-        match f {
-            Child => Self::Child,
-            Parent { child } => Self::Parent { child }
-        }
-    }
-}
-
-/// XXX: refer to manpages
+// XXX: refer to manpages
 
 /// Locks all current and future mapped memory pages, preventing them from being
 /// swapped to disk, and disables process core dumps.
@@ -61,21 +39,14 @@ pub fn expose_mem() -> Result<()> {
     Ok(())
 }
 
-/// # Safety
-///
-/// This function is completely safe if called from a single-threaded process.
-/// However, the newly created process is not an exact duplicate of the
-/// original. For example, it does not inherit its parent's memory locks (such
-/// as those applied by [`secure_mem`]). Further differences are available at
-/// the [`fork(2)`] man page.
-///
-/// If called from a multi-threaded program, undefined behaviour is possible
-/// under circumstances outlined in the documentation for [`unistd::fork`]. In
-/// particular, only async safe functions may be called from the child process.
-///
-/// [`fork(2)`]: https://man7.org/linux/man-pages/man2/fork.2.html
-pub unsafe fn fork() -> Result<Process> {
-    Ok(unistd::fork()?.into())
+/// Sends `sig` to the process `pid`.
+pub fn send_signal(pid: Pid, sig: Signal) -> Result<()> {
+    nix::sys::signal::kill(pid, sig)
+}
+
+/// Returns whether the process `pid` exists, even if it cannot be signalled.
+pub fn exists(pid: Pid) -> bool {
+    nix::sys::signal::kill(pid, None) != Err(Error::ESRCH)
 }
 
 fn disable_dumps() -> Result<()> {
@@ -0,0 +1,664 @@
+use super::secret::Secret;
+
+use std::fmt::Write;
+
+/// The number of error correction codewords in each block, by version. Codes
+/// use the medium error correction level, recovering about 15% of the data.
+const ECC_PER_BLOCK: [usize; 41] = [
+    0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26,
+    26, 26, 26, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+    28, 28, 28
+];
+
+/// The number of error correction blocks, by version.
+const BLOCKS: [usize; 41] = [
+    0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17,
+    18, 20, 21, 23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49
+];
+
+/// The format bits of the medium error correction level.
+const ECC_FORMAT_BITS: u32 = 0;
+
+/// The width in modules of the light border around codes, as required by the
+/// standard.
+const QUIET_ZONE: usize = 4;
+
+/// A QR code, as specified by ISO/IEC 18004.
+///
+/// Data is encoded in byte mode with the smallest version that fits it. The
+/// modules are kept in a [`Secret`] buffer, as they may be used to recover the
+/// data.
+pub struct QrCode {
+    /// The width and height in modules.
+    size: usize,
+    /// Whether each module is dark (1) or light (0), row by row.
+    modules: Secret<Vec<u8>>,
+    /// Whether each module is part of a function pattern rather than data.
+    function: Vec<bool>
+}
+
+impl QrCode {
+    /// Encodes `data` into a QR code.
+    ///
+    /// Returns `None` if `data` is too long to fit in any version.
+    pub fn encode(data: &[u8]) -> Option<Self> {
+        let version = (1..=40).find(|&v| {
+            let count_bits = if v <= 9 { 8 } else { 16 };
+            4 + count_bits + data.len() * 8 <= data_codewords(v) * 8
+        })?;
+
+        let codewords = add_ecc(&data_codewords_of(data, version), version);
+        let size = version * 4 + 17;
+
+        let mut result = Self {
+            size,
+            modules: Secret::new(vec![0; size * size]),
+            function: vec![false; size * size]
+        };
+
+        result.draw_function_patterns(version);
+        result.draw_codewords(&codewords);
+
+        // Use the mask with the lowest penalty, as suggested by the standard.
+        let mask = (0..8).min_by_key(|&m| {
+            result.apply_mask(m);
+            result.draw_format_bits(m);
+
+            let penalty = result.penalty();
+            // Masking twice restores the modules.
+            result.apply_mask(m);
+
+            penalty
+        }).unwrap();
+
+        result.apply_mask(mask);
+        result.draw_format_bits(mask);
+
+        Some(result)
+    }
+
+    /// Returns a representation of this code for the terminal.
+    ///
+    /// Each line of text contains two rows of modules drawn with Unicode half
+    /// blocks, in black on white regardless of the terminal's colours.
+    pub fn render(&self) -> Secret<String> {
+        const COLOURS: &str = "\x1b[30;47m";
+        const RESET: &str = "\x1b[0m";
+
+        let width = self.size + QUIET_ZONE * 2;
+        let line_len =
+            COLOURS.len() + width * '█'.len_utf8() + RESET.len() + 1;
+        let capacity = line_len * width.div_ceil(2);
+        let mut result = Secret::new(String::with_capacity(capacity));
+
+        let is_dark = |x: usize, y: usize| {
+            let x = x.checked_sub(QUIET_ZONE);
+            let y = y.checked_sub(QUIET_ZONE);
+
+            let (Some(x), Some(y)) = (x, y) else {
+                return false;
+            };
+
+            x < self.size && y < self.size && self.module(x, y)
+        };
+
+        for y in (0..width).step_by(2) {
+            result.push_str(COLOURS);
+
+            for x in 0..width {
+                result.push(match (is_dark(x, y), is_dark(x, y + 1)) {
+                    (false, false) => ' ',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (true, true) => '█'
+                });
+            }
+
+            // Writing to a `String` cannot fail.
+            writeln!(result, "{RESET}").unwrap();
+        }
+
+        result
+    }
+
+    fn module(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x] != 0
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        let idx = y * self.size + x;
+
+        self.modules[idx] = u8::from(dark);
+        self.function[idx] = true;
+    }
+
+    /// Draws the finder, alignment and timing patterns, and the version
+    /// information. The format bits are reserved, and drawn by
+    /// [`Self::draw_format_bits`].
+    fn draw_function_patterns(&mut self, version: usize) {
+        let size = self.size;
+
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        for (x, y) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            self.draw_finder(x, y);
+        }
+
+        let positions = alignment_positions(version);
+        let last = positions.len().saturating_sub(1);
+
+        for (i, &x) in positions.iter().enumerate() {
+            for (j, &y) in positions.iter().enumerate() {
+                // Overlapping with finder patterns.
+                let corner = [(0, 0), (0, last), (last, 0)].contains(&(i, j));
+
+                if !corner {
+                    self.draw_alignment(x, y);
+                }
+            }
+        }
+
+        // Reserve the format bits.
+        self.draw_format_bits(0);
+
+        if version >= 7 {
+            let mut rem = version as u32;
+
+            for _ in 0..12 {
+                rem = (rem << 1) ^ ((rem >> 11) * 0x1f25);
+            }
+
+            let bits = (version as u32) << 12 | rem;
+
+            for i in 0..18 {
+                let dark = (bits >> i) & 1 != 0;
+                let (a, b) = (size - 11 + i % 3, i / 3);
+
+                self.set_function(a, b, dark);
+                self.set_function(b, a, dark);
+            }
+        }
+    }
+
+    /// Draws a finder pattern centred on `x` and `y`, with its separator.
+    fn draw_finder(&mut self, x: usize, y: usize) {
+        for dy in -4..=4_isize {
+            for dx in -4..=4_isize {
+                let dist = dx.abs().max(dy.abs());
+                let xx = x.checked_add_signed(dx);
+                let yy = y.checked_add_signed(dy);
+
+                if let (Some(xx), Some(yy)) = (xx, yy) {
+                    if xx < self.size && yy < self.size {
+                        self.set_function(xx, yy, dist != 2 && dist != 4);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draws an alignment pattern centred on `x` and `y`.
+    fn draw_alignment(&mut self, x: usize, y: usize) {
+        for dy in 0..5_usize {
+            for dx in 0..5_usize {
+                let dist = dx.abs_diff(2).max(dy.abs_diff(2));
+                self.set_function(x + dx - 2, y + dy - 2, dist != 1);
+            }
+        }
+    }
+
+    /// Draws both copies of the format bits for `mask`.
+    fn draw_format_bits(&mut self, mask: u32) {
+        let size = self.size;
+        let data = ECC_FORMAT_BITS << 3 | mask;
+        let mut rem = data;
+
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+
+        let bits = (data << 10 | rem) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+
+        // Always dark.
+        self.set_function(8, size - 8, true);
+    }
+
+    /// Draws `codewords` in the data area, in the zigzag order of the
+    /// standard.
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size - 1;
+
+        loop {
+            // Skip the vertical timing pattern.
+            if right == 6 {
+                right = 5;
+            }
+
+            for vert in 0..size {
+                for x in [right, right - 1] {
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { size - 1 - vert } else { vert };
+                    let idx = y * size + x;
+
+                    if !self.function[idx] && i < codewords.len() * 8 {
+                        let bit = (codewords[i / 8] >> (7 - i % 8)) & 1;
+                        self.modules[idx] = bit;
+                        i += 1;
+                    }
+                }
+            }
+
+            if right < 2 {
+                break;
+            }
+
+            right -= 2;
+        }
+    }
+
+    /// Inverts the data modules selected by `mask`.
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0
+                };
+
+                let idx = y * self.size + x;
+
+                if invert && !self.function[idx] {
+                    self.modules[idx] ^= 1;
+                }
+            }
+        }
+    }
+
+    /// Returns the penalty score of the modules, which is higher for codes
+    /// harder to read.
+    fn penalty(&self) -> usize {
+        /// A dark module with the proportions of a finder pattern, preceded or
+        /// followed by four light modules.
+        const FINDER_LIKE: [[bool; 11]; 2] = [
+            [
+                true, false, true, true, true, false, true,
+                false, false, false, false
+            ],
+            [
+                false, false, false, false,
+                true, false, true, true, true, false, true
+            ]
+        ];
+
+        let size = self.size;
+        let mut result = 0;
+
+        for transpose in [false, true] {
+            let at = |a: usize, b: usize| match transpose {
+                false => self.module(a, b),
+                true => self.module(b, a)
+            };
+
+            for b in 0..size {
+                let mut run = 1;
+
+                for a in 1..size {
+                    if at(a, b) == at(a - 1, b) {
+                        run += 1;
+
+                        if run == 5 {
+                            result += 3;
+                        } else if run > 5 {
+                            result += 1;
+                        }
+                    } else {
+                        run = 1;
+                    }
+                }
+
+                for a in 0..size.saturating_sub(10) {
+                    let finder_like = FINDER_LIKE.iter()
+                        .any(|p| (0..11).all(|i| at(a + i, b) == p[i]));
+
+                    if finder_like {
+                        result += 40;
+                    }
+                }
+            }
+        }
+
+        for y in 1..size {
+            for x in 1..size {
+                let dark = self.module(x, y);
+
+                if self.module(x - 1, y) == dark
+                    && self.module(x, y - 1) == dark
+                    && self.module(x - 1, y - 1) == dark
+                {
+                    result += 3;
+                }
+            }
+        }
+
+        let total = size * size;
+        let dark = self.modules.iter().filter(|&&m| m != 0).count();
+        // The deviation from half the modules being dark, in steps of 5%.
+        let k = (dark * 20).abs_diff(total * 10)
+            .div_ceil(total)
+            .saturating_sub(1);
+
+        result + k * 10
+    }
+}
+
+/// Returns the number of data codewords of `version`, excluding error
+/// correction.
+fn data_codewords(version: usize) -> usize {
+    raw_modules(version) / 8 - ECC_PER_BLOCK[version] * BLOCKS[version]
+}
+
+/// Returns the number of modules available for codewords in `version`.
+fn raw_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+
+    if version >= 2 {
+        let aligns = version / 7 + 2;
+        result -= (25 * aligns - 10) * aligns - 55;
+
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+
+    result
+}
+
+/// Returns the centres of the alignment patterns of `version` along each
+/// axis.
+fn alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+
+    let aligns = version / 7 + 2;
+    let step = (version * 8 + aligns * 3 + 5) / (aligns * 4 - 4) * 2;
+    let last = version * 4 + 10;
+
+    // The first is always 6, and the others are evenly spaced up to the last.
+    (0..aligns)
+        .map(|i| match i {
+            0 => 6,
+            _ => last - (aligns - 1 - i) * step
+        })
+        .collect()
+}
+
+/// Returns the data codewords encoding `data` in byte mode, padded to the
+/// capacity of `version`.
+fn data_codewords_of(data: &[u8], version: usize) -> Secret<Vec<u8>> {
+    let capacity = data_codewords(version);
+    let count_bits = if version <= 9 { 8 } else { 16 };
+
+    let mut result = Secret::new(Vec::with_capacity(capacity));
+    let mut acc = 0_u32;
+    let mut acc_len = 0;
+
+    let mut push_bits = |result: &mut Vec<u8>, val: u32, len: u32| {
+        for i in (0..len).rev() {
+            acc = acc << 1 | ((val >> i) & 1);
+            acc_len += 1;
+
+            if acc_len == 8 {
+                result.push(acc as u8);
+                (acc, acc_len) = (0, 0);
+            }
+        }
+    };
+
+    // Byte mode indicator.
+    push_bits(&mut result, 0b0100, 4);
+    push_bits(&mut result, data.len() as u32, count_bits);
+
+    for &b in data {
+        push_bits(&mut result, u32::from(b), 8);
+    }
+
+    // Terminator, up to 4 bits, then padding to a byte boundary. The version
+    // fits the mode, count and data, so at least 4 bits are left in total.
+    let used = 4 + count_bits as usize + data.len() * 8;
+    let terminator = (capacity * 8 - used).min(4) as u32;
+
+    push_bits(&mut result, 0, terminator);
+    let padding = (8 - (used + terminator as usize) % 8) as u32 % 8;
+    push_bits(&mut result, 0, padding);
+
+    for pad in [0xec, 0x11].into_iter().cycle() {
+        if result.len() == capacity {
+            break;
+        }
+
+        result.push(pad);
+    }
+
+    result
+}
+
+/// Returns `data` split into blocks with error correction codewords appended,
+/// interleaved as required by the standard.
+fn add_ecc(data: &[u8], version: usize) -> Secret<Vec<u8>> {
+    let blocks = BLOCKS[version];
+    let ecc_len = ECC_PER_BLOCK[version];
+    let raw = raw_modules(version) / 8;
+    let short_blocks = blocks - raw % blocks;
+    let short_len = raw / blocks;
+    let divisor = rs_divisor(ecc_len);
+
+    let mut result = Secret::new(Vec::with_capacity(raw));
+    let mut split = Vec::with_capacity(blocks);
+    let mut start = 0;
+
+    for i in 0..blocks {
+        let len = short_len - ecc_len + usize::from(i >= short_blocks);
+        let block = &data[start..(start + len)];
+        split.push((block, rs_remainder(block, &divisor)));
+        start += len;
+    }
+
+    // Data codewords, with short blocks skipped once exhausted.
+    for i in 0..=(short_len - ecc_len) {
+        for (dat, _) in &split {
+            if let Some(&b) = dat.get(i) {
+                result.push(b);
+            }
+        }
+    }
+
+    for i in 0..ecc_len {
+        for (_, ecc) in &split {
+            result.push(ecc[i]);
+        }
+    }
+
+    result
+}
+
+/// Returns the Reed-Solomon generator polynomial of `degree`, without its
+/// leading term.
+fn rs_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0; degree];
+    let mut root = 1;
+
+    result[degree - 1] = 1;
+
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_mul(result[j], root);
+
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+
+        root = gf_mul(root, 0x02);
+    }
+
+    result
+}
+
+/// Returns the Reed-Solomon error correction codewords of `data`.
+fn rs_remainder(data: &[u8], divisor: &[u8]) -> Secret<Vec<u8>> {
+    let mut result = Secret::new(vec![0; divisor.len()]);
+
+    for &b in data {
+        let factor = b ^ result[0];
+
+        result.rotate_left(1);
+        *result.last_mut().unwrap() = 0;
+
+        for (r, &d) in result.iter_mut().zip(divisor) {
+            *r ^= gf_mul(d, factor);
+        }
+    }
+
+    result
+}
+
+/// Multiplies `x` and `y` in the Galois field GF(2^8) of the standard.
+fn gf_mul(x: u8, y: u8) -> u8 {
+    let mut result = 0_u32;
+
+    for i in (0..8).rev() {
+        result = (result << 1) ^ ((result >> 7) * 0x11d);
+        result ^= ((u32::from(y) >> i) & 1) * u32::from(x);
+    }
+
+    result as u8
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the modules of `code` row by row, with dark ones as `#`.
+    fn rows(code: &QrCode) -> Vec<String> {
+        (0..code.size)
+            .map(|y| (0..code.size)
+                .map(|x| if code.module(x, y) { '#' } else { '.' })
+                .collect())
+            .collect()
+    }
+
+    #[test]
+    fn encode_matches_reference() {
+        // Produced by an independent encoder, with the same mask (6).
+        const REFERENCE: [&str; 21] = [
+            "#######.#...#.#######",
+            "#.....#.#..##.#.....#",
+            "#.###.#.###...#.###.#",
+            "#.###.#..####.#.###.#",
+            "#.###.#.##..#.#.###.#",
+            "#.....#..#.##.#.....#",
+            "#######.#.#.#.#######",
+            ".........####........",
+            "#..######...##..#.###",
+            ".#..#...##..#.#...#..",
+            "####.###.##...##..###",
+            "....##..#..#.#....#..",
+            ".#.#..#...#....##..##",
+            "........##..#.##..#..",
+            "#######.###.##..##...",
+            "#.....#.#.#.#######.#",
+            "#.###.#.##..#..#...#.",
+            "#.###.#.###..#.####..",
+            "#.###.#..##.#.#.#..##",
+            "#.....#..#.#..#...###",
+            "#######.###.#.###...."
+        ];
+
+        let code = QrCode::encode(b"pass QR test").unwrap();
+
+        assert_eq!(rows(&code), REFERENCE);
+    }
+
+    #[test]
+    fn encode_picks_smallest_version() {
+        // Version 1 holds 16 data codewords, of which 2 are taken by the mode,
+        // count and terminator.
+        assert_eq!(QrCode::encode(&[b'a'; 14]).unwrap().size, 21);
+        assert_eq!(QrCode::encode(&[b'a'; 15]).unwrap().size, 25);
+
+        // The capacity of version 40 in byte mode.
+        assert_eq!(QrCode::encode(&[b'a'; 2331]).unwrap().size, 177);
+        assert!(QrCode::encode(&[b'a'; 2332]).is_none());
+    }
+
+    #[test]
+    fn gf_mul_known_products() {
+        assert_eq!(gf_mul(0, 0x53), 0);
+        assert_eq!(gf_mul(1, 0x53), 0x53);
+        assert_eq!(gf_mul(3, 7), 9);
+        // Powers of 2 wrap around with the polynomial 0x11d.
+        assert_eq!(gf_mul(0x80, 2), 0x1d);
+        assert_eq!(gf_mul(0x8f, 2), 0x03);
+        assert_eq!(gf_mul(0x53, 0xca), gf_mul(0xca, 0x53));
+    }
+
+    #[test]
+    fn rs_remainder_known_codewords() {
+        // "01234567" in version 1-M, from the standard.
+        let data = [
+            0x10, 0x20, 0x0c, 0x56, 0x61, 0x80, 0xec, 0x11,
+            0xec, 0x11, 0xec, 0x11, 0xec, 0x11, 0xec, 0x11
+        ];
+        let ecc = [0xa5, 0x24, 0xd4, 0xc1, 0xed, 0x36, 0xc7, 0x87, 0x2c, 0x55];
+
+        assert_eq!(*rs_remainder(&data, &rs_divisor(10)), ecc);
+
+        // "HELLO WORLD" in version 1-Q.
+        let data = [32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236];
+        let ecc = [168, 72, 22, 82, 217, 54, 156, 0, 46, 15, 180, 122, 16];
+
+        assert_eq!(*rs_remainder(&data, &rs_divisor(13)), ecc);
+    }
+
+    #[test]
+    fn alignment_positions_of_versions() {
+        assert_eq!(alignment_positions(1), []);
+        assert_eq!(alignment_positions(2), [6, 18]);
+        assert_eq!(alignment_positions(7), [6, 22, 38]);
+        assert_eq!(alignment_positions(32), [6, 34, 60, 86, 112, 138]);
+    }
+}
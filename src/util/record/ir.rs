@@ -1,6 +1,6 @@
 //! XXX: intermediate representation
 
-use super::{Record, Error, Node};
+use super::{Record, Error, Node, Value};
 
 use crate::util::secret::Erase;
 use crate::util::secret::Secret;
@@ -11,10 +11,7 @@ use std::fmt;
 
 use std::fmt::Display;
 
-use std::{
-    collections::BTreeMap,
-    rc::Rc
-};
+use std::rc::Rc;
 
 /// XXX: intermediate representation
 #[derive(Serialize, Deserialize)]
@@ -22,18 +19,23 @@ pub enum Ir {
     Group {
         name: String,
         members: Vec<Ir>,
-        #[allow(unused)]    // May be useful later.
         metadata: Metadata
     },
     Item {
         name: String,
         value: String,
-        #[allow(unused)]
+        metadata: Metadata
+    },
+    /// An item containing binary data, stored as base64.
+    Attachment {
+        name: String,
+        #[serde(with = "base64_data")]
+        data: Vec<u8>,
         metadata: Metadata
     }
 }
 
-type Metadata = BTreeMap<String, String>;
+type Metadata = super::Attrs;
 
 type Result<T> = std::result::Result<T, Error>;
 
@@ -41,7 +43,8 @@ impl Ir {
     pub fn name(&self) -> &str {
         match self {
             Self::Group { name, .. } => name,
-            Self::Item { name, .. } => name
+            Self::Item { name, .. } => name,
+            Self::Attachment { name, .. } => name
         }
     }
 
@@ -57,17 +60,27 @@ impl Ir {
                 Self::Group {
                     name: g.meta.name.clone(),
                     members,
-                    metadata: BTreeMap::new()
+                    metadata: g.meta.attrs.clone()
                 }
             }
 
             Record::Item(i) => {
                 let i = i.borrow();
-
-                Self::Item {
-                    name: i.meta.name.clone(),
-                    value: i.value.clone(),
-                    metadata: BTreeMap::new()
+                let name = i.meta.name.clone();
+                let metadata = i.meta.attrs.clone();
+
+                match &i.value {
+                    Value::Text(t) => Self::Item {
+                        name,
+                        value: t.clone(),
+                        metadata
+                    },
+
+                    Value::Binary(b) => Self::Attachment {
+                        name,
+                        data: b.clone(),
+                        metadata
+                    }
                 }
             }
         }
@@ -103,17 +116,26 @@ impl From<Record> for Ir {
                 Self::Group {
                     name: g.meta.name,
                     members,
-                    metadata: BTreeMap::new()
+                    metadata: g.meta.attrs
                 }
             }
 
             Record::Item(i) => {
                 let i = take(i);
-
-                Self::Item {
-                    name: i.meta.name,
-                    value: i.value,
-                    metadata: BTreeMap::new()
+                let (name, metadata) = (i.meta.name, i.meta.attrs);
+
+                match i.value {
+                    Value::Text(value) => Self::Item {
+                        name,
+                        value,
+                        metadata
+                    },
+
+                    Value::Binary(data) => Self::Attachment {
+                        name,
+                        data,
+                        metadata
+                    }
                 }
             }
         }
@@ -143,16 +165,22 @@ impl Erase for Ir {
     #[inline(never)]
     fn erase(&mut self) {
         match self {
-            Self::Group { name, members, metadata: _ } => {
+            Self::Group { name, members, metadata } => {
                 name.erase();
                 members.erase();
-                //metadata.erase();
+                metadata.erase();
             }
 
-            Self::Item { name, value, metadata: _ } => {
+            Self::Item { name, value, metadata } => {
                 name.erase();
                 value.erase();
-                //metadata.erase();
+                metadata.erase();
+            }
+
+            Self::Attachment { name, data, metadata } => {
+                name.erase();
+                data.erase();
+                metadata.erase();
             }
         }
     }
@@ -164,3 +192,62 @@ fn take<T>(v: Node<T>) -> T {
         .unwrap_or_else(|_| panic!("Rc is owned by someone else"))
         .into_inner()
 }
+
+/// Serialisation of binary data as base64 text.
+///
+/// Intermediate buffers are erased after use, and sized in advance such that
+/// they are never reallocated (which would leave unerased copies behind).
+mod base64_data {
+    use crate::util::secret::Secret;
+
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD as BASE64;
+
+    use serde::{Serializer, Deserializer};
+
+    use serde::de::{self, Visitor};
+
+    use std::fmt;
+
+    pub fn serialize<S>(data: &[u8], ser: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer
+    {
+        let mut encoded = Secret::new(String::with_capacity(
+            base64::encoded_len(data.len(), true).unwrap_or_default()
+        ));
+
+        BASE64.encode_string(data, &mut encoded);
+        ser.serialize_str(&encoded)
+    }
+
+    pub fn deserialize<'de, D>(de: D) -> Result<Vec<u8>, D::Error>
+        where
+            D: Deserializer<'de>
+    {
+        de.deserialize_str(Base64Visitor)
+    }
+
+    struct Base64Visitor;
+
+    impl<'de> Visitor<'de> for Base64Visitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("base64 encoded data")
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+            let mut result = Secret::new(
+                vec![0_u8; base64::decoded_len_estimate(s.len())]
+            );
+
+            let len = BASE64.decode_slice(s, &mut result)
+                .map_err(E::custom)?;
+
+            // Truncating never reallocates.
+            result.truncate(len);
+            Ok(result.into_inner())
+        }
+    }
+}
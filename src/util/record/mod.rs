@@ -36,25 +36,40 @@ pub struct Group {
 }
 
 pub struct Item {
-    value: String,
+    value: Value,
     meta: Metadata
 }
 
+/// The data contained within an item.
+pub enum Value {
+    /// Textual data, like a password or username.
+    Text(String),
+    /// Raw binary data, like the contents of a key file. Called an attachment.
+    Binary(Vec<u8>)
+}
+
 pub struct Metadata {
     /// XXX: should not modified be if a parent exists (to avoid invalidating
     /// the hashmap)
     name: String,
     parent: Option<WeakNode<Group>>,
+    /// Arbitrary named values kept along with the record, like password
+    /// policies.
+    attrs: Attrs
 }
 
+pub type Attrs = BTreeMap<String, String>;
+
 #[derive(Debug)]
 pub enum Error {
     // Clippy recommends that we box this (very large) error.
     Serialisation(Box<ron::error::Error>),
     Deserialisation(Box<ron::error::SpannedError>),
     NotFound,
-    MultipleMatches,
+    /// Contains the names of the records matching equally well.
+    MultipleMatches(Vec<String>),
     AlreadyExists,
+    InvalidName,
 }
 
 pub type Node<T> = Rc<RefCell<T>>;
@@ -66,10 +81,10 @@ pub type Result<T> = std::result::Result<T, Error>;
 impl Record {
     pub fn from(ir: Ir) -> Node<Self> {
         match ir {
-            Ir::Group { name, members, metadata: _ } => {
+            Ir::Group { name, members, metadata } => {
                 let group = new_node(Group {
                     members: BTreeMap::new(),
-                    meta: Metadata::for_root(name)
+                    meta: Metadata::for_root(name, metadata)
                 });
 
                 group.borrow_mut().members = members.into_iter().map(|ir| {
@@ -84,7 +99,7 @@ impl Record {
                     // reference is valid. Furthermore, the record and its name
                     // will never be moved as it is kept behind an `Rc`.
                     let name = unsafe {
-                        std::mem::transmute::<_, &'static str>(ir.name())
+                        std::mem::transmute::<&str, &'static str>(ir.name())
                     };
 
                     let rec = Record::with_parent(ir, &group);
@@ -95,10 +110,17 @@ impl Record {
                 new_node(Record::Group(group))
             }
 
-            Ir::Item { name, value, metadata: _ } => {
+            Ir::Item { name, value, metadata } => {
+                new_node(Record::Item(new_node(Item {
+                    value: Value::Text(value),
+                    meta: Metadata::for_root(name, metadata)
+                })))
+            }
+
+            Ir::Attachment { name, data, metadata } => {
                 new_node(Record::Item(new_node(Item {
-                    value,
-                    meta: Metadata::for_root(name)
+                    value: Value::Binary(data),
+                    meta: Metadata::for_root(name, metadata)
                 })))
             }
         }
@@ -108,7 +130,7 @@ impl Record {
         new_node(Record::Group(Group::new(name)))
     }
 
-    pub fn new_item(name: String, value: String) -> Node<Self> {
+    pub fn new_item(name: String, value: Value) -> Node<Self> {
         new_node(Record::Item(Item::new(name, value)))
     }
 
@@ -154,7 +176,7 @@ impl Group {
     pub fn new(name: String) -> Node<Self> {
         new_node(Self {
             members: BTreeMap::new(),
-            meta: Metadata::for_root(name)
+            meta: Metadata::for_root(name, Attrs::new())
         })
     }
 
@@ -162,10 +184,23 @@ impl Group {
         self.meta.name()
     }
 
+    pub fn attrs(&self) -> &Attrs {
+        &self.meta.attrs
+    }
+
+    pub fn attrs_mut(&mut self) -> &mut Attrs {
+        &mut self.meta.attrs
+    }
+
     pub fn parent(&self) -> Option<Node<Group>> {
         self.meta.parent()
     }
 
+    /// Returns the members of the group with their names, ordered by name.
+    pub fn members(&self) -> impl Iterator<Item = (&str, &Node<Record>)> {
+        self.members.iter().map(|(name, rec)| (*name, rec))
+    }
+
     pub fn get(&self, name: &str) -> Result<Node<Record>> {
         let result = self.members
             .get(name)
@@ -174,42 +209,68 @@ impl Group {
         Ok(Rc::clone(result))
     }
 
-    pub fn get_fuzzy(&self, name_pat: &str) -> Result<Node<Record>> {
-        let mut first_match = Option::<Match>::None;
-        let mut members_iter = self.members.iter();
-
-        for (name, rec) in &mut members_iter {
-            if let Some(m) = Match::make(name_pat, name, rec) {
-                first_match = Some(m);
-                break;
-            }
+    /// XXX: returns the only member whose name satisfies `pred`
+    /// - a member named exactly `name_pat` is preferred
+    /// - fails with the names of the matches if there are several
+    pub fn get_matching<P>(
+        &self,
+        name_pat: &str,
+        pred: P
+    ) -> Result<Node<Record>>
+        where
+            P: Fn(&str) -> bool
+    {
+        if let Some(rec) = self.members.get(name_pat) {
+            return Ok(Rc::clone(rec));
         }
 
-        let mut best_match = first_match
-            .ok_or(Error::NotFound)?;
+        let matches = self.members.iter()
+            .filter(|(name, _)| pred(name))
+            .collect::<Vec<_>>();
 
-        let mut have_multiple_matches = false;
+        match matches.as_slice() {
+            [] => Err(Error::NotFound),
+            [(_, rec)] => Ok(Rc::clone(rec)),
 
-        // Iterate through the remaining `Records`s.
-        for (name, rec) in members_iter {
-            if let Some(m) = Match::make(name_pat, name, rec) {
-                match m.cmp_score(&best_match) {
-                    Ordering::Greater => {
-                        best_match = m;
-                        // No other match exists yet at this new highest score.
-                        have_multiple_matches = false;
-                    }
+            _ => Err(Error::MultipleMatches(
+                matches.into_iter()
+                    .map(|(name, _)| (*name).to_owned())
+                    .collect()
+            ))
+        }
+    }
+
+    /// XXX: fails with the names of the best matches if there are several
+    pub fn get_fuzzy(&self, name_pat: &str) -> Result<Node<Record>> {
+        // The matches with the highest score so far, with their names.
+        let mut best_matches = Vec::<(&str, Match)>::new();
 
-                    Ordering::Equal => have_multiple_matches = true,
-                    Ordering::Less => continue
+        for (name, rec) in &self.members {
+            let Some(m) = Match::make(name_pat, name, rec) else {
+                continue;
+            };
+
+            match best_matches.first().map(|(_, best)| m.cmp_score(best)) {
+                None | Some(Ordering::Greater) => {
+                    // No other match exists yet at this new highest score.
+                    best_matches.clear();
+                    best_matches.push((name, m));
                 }
+
+                Some(Ordering::Equal) => best_matches.push((name, m)),
+                Some(Ordering::Less) => continue
             }
         }
 
-        if have_multiple_matches {
-            Err(Error::MultipleMatches)
-        } else {
-            Ok(Rc::clone(best_match.val))
+        match best_matches.as_slice() {
+            [] => Err(Error::NotFound),
+            [(_, m)] => Ok(Rc::clone(m.val)),
+
+            _ => Err(Error::MultipleMatches(
+                best_matches.iter()
+                    .map(|(name, _)| (*name).to_owned())
+                    .collect()
+            ))
         }
     }
 
@@ -224,6 +285,10 @@ impl Group {
 
             // If the insertion cannot be done, we return before modifying
             // 'rec'.
+            if !is_valid_name(name) {
+                return Err(Error::InvalidName);
+            }
+
             if members.contains_key(name) {
                 return Err(Error::AlreadyExists);
             }
@@ -232,7 +297,7 @@ impl Group {
 
             // SAFETY: Same as with `Record::from`.
             Ok(unsafe {
-                mem::transmute::<_, &'static str>(name)
+                mem::transmute::<&str, &'static str>(name)
             })
         })?;
 
@@ -261,10 +326,10 @@ impl Erase for Group {
 }
 
 impl Item {
-    pub fn new(name: String, value: String) -> Node<Self> {
+    pub fn new(name: String, value: Value) -> Node<Self> {
         new_node(Self {
             value,
-            meta: Metadata::for_root(name)
+            meta: Metadata::for_root(name, Attrs::new())
         })
     }
 
@@ -272,11 +337,11 @@ impl Item {
         self.meta.name()
     }
 
-    pub fn value(&self) -> &String {
+    pub fn value(&self) -> &Value {
         &self.value
     }
 
-    pub fn value_mut(&mut self) -> &mut String {
+    pub fn value_mut(&mut self) -> &mut Value {
         &mut self.value
     }
 
@@ -293,6 +358,34 @@ impl Erase for Item {
     }
 }
 
+impl Value {
+    /// Returns the contained text, or `None` if this is an attachment.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text(t) => Some(t),
+            Self::Binary(_) => None
+        }
+    }
+
+    /// Returns the raw bytes of the contained data, whatever its kind.
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Text(t) => t.as_bytes(),
+            Self::Binary(b) => b
+        }
+    }
+}
+
+impl Erase for Value {
+    #[inline(never)]
+    fn erase(&mut self) {
+        match self {
+            Self::Text(t) => t.erase(),
+            Self::Binary(b) => b.erase()
+        }
+    }
+}
+
 impl Metadata {
     pub fn name(&self) -> &str {
         &self.name
@@ -310,6 +403,7 @@ impl Erase for Metadata {
     #[inline(never)]
     fn erase(&mut self) {
         self.name.erase();
+        self.attrs.erase();
     }
 }
 
@@ -324,14 +418,24 @@ impl Display for Error {
                 write!(f, "{e}"),
             NotFound =>
                 write!(f, "record not found"),
-            MultipleMatches =>
+            MultipleMatches(_) =>
                 write!(f, "multiple matches found"),
             AlreadyExists =>
                 write!(f, "record already exists"),
+            InvalidName =>
+                write!(f, "invalid record name"),
         }
     }
 }
 
+/// Verifies if `name` is valid as the name of a record within a group.
+///
+/// Any non-empty name is valid, as it can always be reached with an escaped
+/// [`RecordPath`][crate::find::RecordPath].
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+}
+
 impl Record {
     fn with_parent(ir: Ir, parent: &Node<Group>) -> Node<Self> {
         let result = Record::from(ir);
@@ -359,8 +463,8 @@ impl Record {
 }
 
 impl Metadata {
-    fn for_root(name: String) -> Self {
-        Self { name, parent: None }
+    fn for_root(name: String, attrs: Attrs) -> Self {
+        Self { name, parent: None, attrs }
     }
 }
 
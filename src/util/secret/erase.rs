@@ -68,6 +68,17 @@ impl Erase for Vec<u8> {
     }
 }
 
+impl Erase for Vec<char> {
+    #[inline(never)]
+    fn erase(&mut self) {
+        for v in self.as_mut_slice() {
+            set_volatile(v, '\0');
+        }
+
+        atomic_fence();
+    }
+}
+
 impl Erase for String {
     #[inline(never)]
     fn erase(&mut self) {
@@ -80,7 +91,7 @@ impl Erase for String {
     }
 }
 
-impl<'k, V: Erase> Erase for BTreeMap<&'k str, V> {
+impl<V: Erase> Erase for BTreeMap<&str, V> {
     #[inline(never)]
     fn erase(&mut self) {
         for v in self.values_mut() {
@@ -7,6 +7,8 @@ use nix::sys::termios::{
 
 use std::{fmt, io};
 
+use std::time::Duration;
+
 use std::{
     io::ErrorKind::UnexpectedEof,
     io::Stdin
@@ -108,8 +110,6 @@ macro_rules! confirm {
         use $crate::util::user_io::Result;
         use $crate::input;
 
-        use ::std::format_args;
-
         let result: Result<bool> = loop {
             let input_res = input!(
                 "{} [y/n] ",
@@ -142,11 +142,9 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub fn get_line(prompt: fmt::Arguments) -> Result<String> {
     eprint!("{prompt}");
 
-    let mut line = read_line().map_err(|e| {
+    let mut line = read_line().inspect_err(|e| {
         // Simulate a newline if the user closed the stream.
         if e.kind() == UnexpectedEof { eprintln!() };
-
-        e
     })?;
 
     // Remove the trailing newline entered by the user. `read_line()` always
@@ -168,6 +166,76 @@ pub fn read_stdin() -> Result<String> {
     Ok(result)
 }
 
+/// Waits at most `time` for input on standard input.
+///
+/// Returns whether input is available. On a terminal, this is only the case
+/// once a line was entered.
+pub fn wait_stdin(time: Duration) -> Result<bool> {
+    use nix::poll::{self, PollFd, PollFlags};
+
+    use std::os::fd::AsFd;
+
+    let stdin = io::stdin();
+    let fd = stdin.as_fd();
+    let millis = time.as_millis().try_into().unwrap_or(i32::MAX);
+
+    let n = poll::poll(&mut [PollFd::new(&fd, PollFlags::POLLIN)], millis)?;
+
+    Ok(n > 0)
+}
+
+/// Displays `text` on standard output for `time` or until a key is pressed,
+/// then erases it along with the terminal's scrollback.
+///
+/// Standard output should be a terminal. Keys are read from standard input
+/// without being echoed, and `time` runs out regardless if it isn't a terminal.
+pub fn display_timed(text: &str, time: Duration) -> Result<()> {
+    use std::io::{Read, Write, IsTerminal};
+
+    let mut stdout = io::stdout().lock();
+
+    stdout.write_all(text.as_bytes())?;
+    stdout.flush()?;
+
+    if io::stdin().is_terminal() {
+        let old_term = termios::tcgetattr(io::stdin())?;
+
+        // Any key including Ctrl-C erases the text, without waiting for a
+        // newline.
+        mutate_termios(io::stdin(), |term| {
+            term.local_flags.remove(
+                LocalFlags::ICANON | LocalFlags::ECHO | LocalFlags::ISIG
+            );
+        })?;
+
+        let result = wait_stdin(time).and_then(|pressed| match pressed {
+            true => io::stdin().read(&mut [0; 16]).map(drop),
+            false => Ok(())
+        });
+
+        termios::tcsetattr(io::stdin(), termios::SetArg::TCSANOW, &old_term)?;
+        result?;
+    } else {
+        std::thread::sleep(time);
+    }
+
+    let width = term_width().unwrap_or(usize::MAX);
+
+    // Lines longer than the terminal are wrapped over several rows.
+    let rows = text.lines()
+        .map(|l| columns(l).div_ceil(width).max(1))
+        .sum::<usize>();
+
+    // Move to the first row, then erase the rest of the screen and the
+    // scrollback.
+    if rows > 0 {
+        write!(stdout, "\x1b[{rows}F")?;
+    }
+
+    write!(stdout, "\x1b[J\x1b[3J")?;
+    stdout.flush()
+}
+
 /// XXX: hides user input henceforth
 ///   useful for reading sensitive data, like passwords
 pub fn hide_input() -> Result<()> {
@@ -199,6 +267,40 @@ fn read_line() -> Result<String> {
     }
 }
 
+/// Returns the number of columns taken by `line` on a terminal, assuming each
+/// character takes one. Escape sequences like colour changes take none.
+fn columns(line: &str) -> usize {
+    let mut chars = line.chars();
+    let mut result = 0;
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.clone().next() == Some('[') {
+            // Skip the parameters, up to the final byte.
+            chars.by_ref().skip(1).find(|c| ('\x40'..='\x7e').contains(c));
+        } else {
+            result += 1;
+        }
+    }
+
+    result
+}
+
+/// Returns the width in columns of the terminal of standard output, if it is
+/// one.
+fn term_width() -> Option<usize> {
+    use nix::libc::{self, winsize, TIOCGWINSZ};
+
+    let mut size = winsize { ws_row: 0, ws_col: 0, ws_xpixel: 0, ws_ypixel: 0 };
+
+    // SAFETY: `TIOCGWINSZ` only writes a `winsize` to the passed pointer, which
+    // is valid.
+    let ret = unsafe {
+        libc::ioctl(libc::STDOUT_FILENO, TIOCGWINSZ, &mut size)
+    };
+
+    (ret == 0 && size.ws_col > 0).then_some(size.ws_col.into())
+}
+
 /// XXX
 /// mutates termios of `fd` (usually stdin/stdout/stderr) with `op`
 /// changes are applied immediately (`TCSANOW`)
@@ -30,3 +30,14 @@ pub fn data_dir(name: &str) -> Result<PathBuf> {
     Ok(BaseDirectories::with_prefix(name)?
         .get_data_home())
 }
+
+/// Returns a path to the `name`-specific XDG runtime directory.
+///
+/// Fails if there is no secure runtime directory.
+pub fn runtime_dir(name: &str) -> Result<PathBuf> {
+    use xdg::BaseDirectories;
+
+    Ok(BaseDirectories::new()?
+        .get_runtime_directory()?
+        .join(name))
+}